@@ -0,0 +1,331 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::Instruction,
+        program::{ get_return_data, invoke, set_return_data },
+        program_option::COption,
+        sysvar::instructions::{ load_current_index_checked, load_instruction_at_checked },
+    },
+};
+use anchor_spl::token_interface::{
+    spl_token_2022::{
+        self,
+        extension::{
+            default_account_state::DefaultAccountState,
+            permanent_delegate::PermanentDelegate,
+            transfer_fee::instruction::TransferFeeInstruction,
+            BaseStateWithExtensions,
+            StateWithExtensions,
+        },
+        instruction::TokenInstruction,
+        state::{ AccountState, Mint as MintState },
+    },
+    TokenAccount,
+};
+use crate::state::{
+    approval::SignedApproval,
+    config::Config,
+    attestation::{ Attestation, KycProviders },
+    holding_limits::HoldingLimits,
+    memo_policy::MemoPolicy,
+    registry::SecurityStatus,
+    rule_modules::{ RuleModule, RuleModules, CHECK_TRANSFER_DISCRIMINATOR },
+    whitelist::WhiteList,
+};
+use crate::errors::{ HoldingLimitError, MemoError, RuleModuleError, TransferError };
+use crate::settlement::is_order_escrow;
+use crate::TransferHook;
+
+// ERC-1404 style restriction codes, 0 means the transfer is allowed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum RestrictionCode {
+    Success = 0,
+    DestinationNotAllowed = 1,
+    SecurityNotActive = 2,
+    RuleModuleRejected = 3,
+    HoldingLimitExceeded = 4,
+}
+
+impl RestrictionCode {
+    pub fn message(&self) -> &'static str {
+        match self {
+            RestrictionCode::Success => "SUCCESS",
+            RestrictionCode::DestinationNotAllowed =>
+                "The receiving account is not whitelisted, attested or approved",
+            RestrictionCode::SecurityNotActive => "The security is draft, halted or matured",
+            RestrictionCode::RuleModuleRejected => "A compliance rule module rejected the transfer",
+            RestrictionCode::HoldingLimitExceeded =>
+                "The receiving account would exceed the holding limits of this security",
+        }
+    }
+
+    pub fn to_error(self) -> Error {
+        match self {
+            RestrictionCode::Success => unreachable!("success is not an error"),
+            RestrictionCode::DestinationNotAllowed => error!(TransferError::DestinationNotAllowed),
+            RestrictionCode::SecurityNotActive => error!(TransferError::SecurityNotActive),
+            RestrictionCode::RuleModuleRejected => error!(TransferError::RuleModuleRejected),
+            RestrictionCode::HoldingLimitExceeded => error!(HoldingLimitError::LimitExceeded),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct TransferRestriction {
+    pub code: u8,
+    pub message: String,
+}
+
+impl From<RestrictionCode> for TransferRestriction {
+    fn from(code: RestrictionCode) -> Self {
+        TransferRestriction {
+            code: code as u8,
+            message: code.message().to_string(),
+        }
+    }
+}
+
+// Rules shared by `transfer_hook` and `detect_transfer_restriction`, so a
+// pre-check can never disagree with the hook. `destination_balance` is the
+// balance of the destination once the transfer has gone through.
+impl<'info> TransferHook<'info> {
+    pub fn detect_restriction(
+        &self,
+        amount: u64,
+        destination_balance: u64,
+        remaining_accounts: &[AccountInfo<'info>]
+    ) -> Result<RestrictionCode> {
+        if self.registry.status != SecurityStatus::Active {
+            return Ok(RestrictionCode::SecurityNotActive);
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let allowed = is_allowed_holder(
+            &self.white_list,
+            &self.kyc_providers,
+            &self.attestation.to_account_info(),
+            &self.destination_token.key(),
+            &self.destination_token.owner,
+            now
+        )?;
+        let allowed =
+            allowed ||
+            is_thawed_holder(&self.mint.to_account_info(), &self.destination_token)? ||
+            is_order_escrow(&self.instructions_sysvar, &self.destination_token.key())?;
+        if !allowed && !self.has_valid_approval(now)? {
+            return Ok(RestrictionCode::DestinationNotAllowed);
+        }
+        let exceeds_limits = exceeds_holding_limits(
+            &self.holding_limits.to_account_info(),
+            &self.destination_token.key(),
+            &self.destination_token.owner,
+            destination_balance
+        )?;
+        if exceeds_limits {
+            return Ok(RestrictionCode::HoldingLimitExceeded);
+        }
+        if !self.passes_rule_modules(amount, remaining_accounts)? {
+            return Ok(RestrictionCode::RuleModuleRejected);
+        }
+
+        Ok(RestrictionCode::Success)
+    }
+
+    // When the issuer requires reason codes, the closest memo placed before the
+    // transfer in its transaction must start with one of them. A memo already
+    // followed by another token transfer belongs to that transfer.
+    pub fn check_reason_memo(&self) -> Result<()> {
+        let memo_policy_info = self.memo_policy.to_account_info();
+        if memo_policy_info.owner != &crate::ID || memo_policy_info.data_is_empty() {
+            return Ok(());
+        }
+        let memo_policy = MemoPolicy::try_deserialize(&mut &memo_policy_info.data.borrow()[..])?;
+        if !memo_policy.required {
+            return Ok(());
+        }
+
+        let instructions_sysvar = self.instructions_sysvar.to_account_info();
+        let current_index = load_current_index_checked(&instructions_sysvar)?;
+        for index in (0..current_index as usize).rev() {
+            let ix = load_instruction_at_checked(index, &instructions_sysvar)?;
+            if is_token_transfer(&ix) {
+                break;
+            }
+            if ix.program_id != spl_memo::id() && ix.program_id != spl_memo::v1::id() {
+                continue;
+            }
+            let reason_code = memo_policy.reason_code(&ix.data).ok_or(MemoError::MissingReasonMemo)?;
+            msg!("Transfer reason: {0}", reason_code);
+            return Ok(());
+        }
+        err!(MemoError::MissingReasonMemo)
+    }
+
+    // Consults the configured rule modules in order, stopping at the first rejection
+    fn passes_rule_modules(&self, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<bool> {
+        let rule_modules_info = self.rule_modules.to_account_info();
+        if rule_modules_info.owner != &crate::ID || rule_modules_info.data_is_empty() {
+            return Ok(true);
+        }
+        let rule_modules = RuleModules::try_deserialize(&mut &rule_modules_info.data.borrow()[..])?;
+        require!(
+            remaining_accounts.len() >= rule_modules.account_len(),
+            RuleModuleError::MissingAccounts
+        );
+
+        let mut offset = 0;
+        for module in rule_modules.modules.iter() {
+            let program = &remaining_accounts[offset];
+            let accounts = &remaining_accounts[offset + 1..offset + 1 + (module.account_count as usize)];
+            offset += 1 + (module.account_count as usize);
+
+            require_keys_eq!(program.key(), module.program, RuleModuleError::ProgramMismatch);
+            if !self.check_rule_module(module, program, accounts, amount)? {
+                msg!("Rejected by rule module {0}", module.program);
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    // Calls `check_transfer(amount)` on the module with the transfer accounts,
+    // read-only, followed by its own. A nonzero u8 returned by the module is a
+    // rejection; a module may also fail outright, which aborts the transfer.
+    fn check_rule_module(
+        &self,
+        module: &RuleModule,
+        program: &AccountInfo<'info>,
+        accounts: &[AccountInfo<'info>],
+        amount: u64
+    ) -> Result<bool> {
+        let mut account_infos = vec![
+            self.source_token.to_account_info(),
+            self.mint.to_account_info(),
+            self.destination_token.to_account_info(),
+            self.owner.to_account_info()
+        ];
+        let mut metas: Vec<AccountMeta> = account_infos
+            .iter()
+            .map(|info| AccountMeta::new_readonly(info.key(), false))
+            .collect();
+        for account in accounts {
+            metas.push(if account.is_writable {
+                AccountMeta::new(account.key(), false)
+            } else {
+                AccountMeta::new_readonly(account.key(), false)
+            });
+            account_infos.push(account.clone());
+        }
+        account_infos.push(program.clone());
+
+        let mut data = CHECK_TRANSFER_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+        // Cleared first, so data left over from an earlier call, even one to the
+        // same program, is never read as this module's answer
+        set_return_data(&[]);
+        invoke(&(Instruction { program_id: module.program, accounts: metas, data }), &account_infos)?;
+
+        match get_return_data() {
+            Some((program_id, data)) if program_id == module.program =>
+                Ok(data.first().copied().unwrap_or_default() == 0),
+            _ => Ok(true),
+        }
+    }
+
+    fn has_valid_approval(&self, now: i64) -> Result<bool> {
+        let approval_info = self.approval.to_account_info();
+        if approval_info.owner != &crate::ID || approval_info.data_is_empty() {
+            return Ok(false);
+        }
+
+        // Only approvals signed by the current compliance key count
+        let config_info = self.config.to_account_info();
+        if config_info.owner != &crate::ID || config_info.data_is_empty() {
+            return Ok(false);
+        }
+        let config = Config::try_deserialize(&mut &config_info.data.borrow()[..])?;
+
+        let approval = SignedApproval::try_deserialize(&mut &approval_info.data.borrow()[..])?;
+        Ok(approval.is_valid(&self.destination_token.owner, &self.mint.key(), &config.compliance_key, now))
+    }
+}
+
+// A token account may hold the security if it is whitelisted or its owner has a
+// valid attestation. The attestation account is derived from the owner, so it
+// may not exist.
+pub fn is_allowed_holder(
+    white_list: &WhiteList,
+    kyc_providers: &KycProviders,
+    attestation_info: &AccountInfo,
+    token_account: &Pubkey,
+    owner: &Pubkey,
+    now: i64
+) -> Result<bool> {
+    if white_list.white_list.contains(token_account) {
+        return Ok(true);
+    }
+    if attestation_info.owner != &crate::ID || attestation_info.data_is_empty() {
+        return Ok(false);
+    }
+
+    let attestation = Attestation::try_deserialize(&mut &attestation_info.data.borrow()[..])?;
+    Ok(attestation.is_valid(owner, kyc_providers, now))
+}
+
+// On a mint whose accounts start frozen, an account thawed by `approve_holder`
+// may hold the security
+pub fn is_thawed_holder(mint: &AccountInfo, token_account: &TokenAccount) -> Result<bool> {
+    let mint_data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+    let default_frozen = mint_state
+        .get_extension::<DefaultAccountState>()
+        .is_ok_and(|extension| extension.state == (AccountState::Frozen as u8));
+    Ok(default_frozen && !token_account.is_frozen())
+}
+
+// Holding limits are optional, the account is derived from the mint and may not exist
+pub fn exceeds_holding_limits(
+    limits_info: &AccountInfo,
+    token_account: &Pubkey,
+    owner: &Pubkey,
+    balance: u64
+) -> Result<bool> {
+    if limits_info.owner != &crate::ID || limits_info.data_is_empty() {
+        return Ok(false);
+    }
+
+    let limits = HoldingLimits::try_deserialize(&mut &limits_info.data.borrow()[..])?;
+    Ok(!limits.is_exempt(token_account, owner) && !limits.allows(balance))
+}
+
+// The deprecated plain `Transfer` still moves tokens of a hooked mint
+#[allow(deprecated)]
+fn is_token_transfer(ix: &Instruction) -> bool {
+    if ix.program_id != spl_token_2022::ID {
+        return false;
+    }
+    match TokenInstruction::unpack(&ix.data) {
+        Ok(TokenInstruction::Transfer { .. } | TokenInstruction::TransferChecked { .. }) => true,
+        Ok(TokenInstruction::TransferFeeExtension) =>
+            matches!(
+                TransferFeeInstruction::unpack(&ix.data[1..]),
+                Ok(TransferFeeInstruction::TransferCheckedWithFee { .. })
+            ),
+        _ => false,
+    }
+}
+
+pub fn is_transfer_authority(source: &TokenAccount, mint: &AccountInfo, authority: &Pubkey) -> Result<bool> {
+    if source.owner == *authority || source.delegate == COption::Some(*authority) {
+        return Ok(true);
+    }
+
+    let mint_data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+    let permanent_delegate = mint_state
+        .get_extension::<PermanentDelegate>()
+        .ok()
+        .and_then(|extension| Option::<Pubkey>::from(extension.delegate));
+    Ok(permanent_delegate == Some(*authority))
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    ed25519_program,
+    sysvar::instructions::{ load_current_index_checked, load_instruction_at_checked },
+};
+use crate::errors::ApprovalError;
+
+// Layout of a single-signature ed25519 program instruction:
+// [num_signatures: u8, padding: u8, offsets: 7 x u16, ...pubkey, signature, message]
+const OFFSETS_START: usize = 2;
+const OFFSETS_LEN: usize = 14;
+const PUBKEY_LEN: usize = 32;
+// wallet (32) || mint (32) || expires_at (i64 le)
+const APPROVAL_MESSAGE_LEN: usize = 72;
+
+// Checks that the instruction right before the current one is an ed25519
+// verification of `wallet || mint || expires_at` signed by `compliance_key`,
+// and returns the signed expiry.
+pub fn verify_compliance_approval(
+    instructions_sysvar: &AccountInfo,
+    compliance_key: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey
+) -> Result<i64> {
+    require_keys_neq!(*compliance_key, Pubkey::default(), ApprovalError::ComplianceKeyNotSet);
+
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, ApprovalError::MissingSignature);
+    let ix = load_instruction_at_checked((current_index - 1) as usize, instructions_sysvar)?;
+    require_keys_eq!(ix.program_id, ed25519_program::ID, ApprovalError::MissingSignature);
+
+    let data = &ix.data;
+    require!(
+        data.len() >= OFFSETS_START + OFFSETS_LEN && data[0] == 1,
+        ApprovalError::InvalidSignatureInstruction
+    );
+
+    let offsets: Vec<u16> = data[OFFSETS_START..OFFSETS_START + OFFSETS_LEN]
+        .chunks_exact(2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .collect();
+    let (signature_ix, pubkey_offset, pubkey_ix) = (offsets[1], offsets[2] as usize, offsets[3]);
+    let (message_offset, message_len, message_ix) = (
+        offsets[4] as usize,
+        offsets[5] as usize,
+        offsets[6],
+    );
+
+    // Everything must live in the ed25519 instruction itself
+    require!(
+        signature_ix == u16::MAX && pubkey_ix == u16::MAX && message_ix == u16::MAX,
+        ApprovalError::InvalidSignatureInstruction
+    );
+    require!(message_len == APPROVAL_MESSAGE_LEN, ApprovalError::MessageMismatch);
+
+    let signer = data
+        .get(pubkey_offset..pubkey_offset + PUBKEY_LEN)
+        .ok_or(ApprovalError::InvalidSignatureInstruction)?;
+    require!(signer == compliance_key.as_ref(), ApprovalError::WrongSigner);
+
+    let message = data
+        .get(message_offset..message_offset + message_len)
+        .ok_or(ApprovalError::InvalidSignatureInstruction)?;
+    require!(
+        &message[..32] == wallet.as_ref() && &message[32..64] == mint.as_ref(),
+        ApprovalError::MessageMismatch
+    );
+
+    let expires_at = i64::from_le_bytes(message[64..72].try_into().unwrap());
+    require!(expires_at > Clock::get()?.unix_timestamp, ApprovalError::ApprovalExpired);
+
+    Ok(expires_at)
+}
@@ -4,4 +4,252 @@ use anchor_lang::prelude::*;
 pub enum TransferError {
     #[msg("The token is not currently transferring")]
     IsNotCurrentlyTransferring,
+    #[msg("The receiving account is not whitelisted, attested or approved")]
+    DestinationNotAllowed,
+    #[msg("Signer is not the transfer hook authority of this mint")]
+    InvalidHookAuthority,
+    #[msg("The security is not active")]
+    SecurityNotActive,
+    #[msg("Transfer authority is not the owner, delegate or permanent delegate of the source")]
+    InvalidTransferAuthority,
+    #[msg("A compliance rule module rejected the transfer")]
+    RuleModuleRejected,
+}
+
+#[error_code(offset = 6100)]
+pub enum SaleError {
+    #[msg("The config already holds the maximum number of tranches")]
+    TooManyTranches,
+    #[msg("Tranche index out of range")]
+    InvalidTranche,
+    #[msg("Tranche end must be after its start")]
+    InvalidTrancheWindow,
+    #[msg("Not enough open tranche allocation for this purchase")]
+    InsufficientTrancheCapacity,
+    #[msg("Purchase would exceed the maximum supply")]
+    MaxSupplyExceeded,
+    #[msg("Mint amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("Payments for this sale are escrowed, use subscribe")]
+    EscrowedSale,
+    #[msg("This sale does not escrow payments")]
+    NotEscrowedSale,
+    #[msg("The sale already has purchases")]
+    SaleAlreadyStarted,
+    #[msg("The sale is not open")]
+    SaleNotOpen,
+    #[msg("The subscription period has not ended")]
+    SaleStillOpen,
+    #[msg("Refunds are only available for failed or cancelled sales")]
+    RefundUnavailable,
+    #[msg("Subscription does not belong to this sale")]
+    InvalidSubscription,
+    #[msg("Subscription already settled")]
+    AlreadySettled,
+    #[msg("Math operation overflow")]
+    MathOverflow,
+    #[msg("Close time must be in the future and after the open tranches end")]
+    InvalidCloseTime,
+}
+
+#[error_code(offset = 6200)]
+pub enum VestingError {
+    #[msg("Vesting schedule timestamps are out of order")]
+    InvalidSchedule,
+    #[msg("Vesting amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("Nothing has vested since the last release")]
+    NothingToRelease,
+    #[msg("The vesting schedule has been revoked")]
+    Revoked,
+    #[msg("Milestone index out of range")]
+    InvalidMilestone,
+    #[msg("Milestone already achieved")]
+    MilestoneAlreadyAchieved,
+    #[msg("Operation only valid for milestone schedules")]
+    NotMilestoneSchedule,
+    #[msg("Math operation overflow")]
+    MathOverflow,
+}
+
+#[error_code(offset = 6300)]
+pub enum AttestationError {
+    #[msg("Only the KYC provider list authority can do this")]
+    Unauthorized,
+    #[msg("The provider is already trusted")]
+    ProviderAlreadyTrusted,
+    #[msg("The provider is not trusted")]
+    UnknownProvider,
+    #[msg("The provider list is full")]
+    TooManyProviders,
+    #[msg("Attestation expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("The investor holds a valid attestation from another provider")]
+    AttestedByOtherProvider,
+}
+
+#[error_code(offset = 6400)]
+pub enum ApprovalError {
+    #[msg("No compliance key is configured for this mint")]
+    ComplianceKeyNotSet,
+    #[msg("Expected an ed25519 signature instruction before this one")]
+    MissingSignature,
+    #[msg("Malformed ed25519 signature instruction")]
+    InvalidSignatureInstruction,
+    #[msg("Approval was not signed by the compliance key")]
+    WrongSigner,
+    #[msg("Signed approval does not match this wallet and mint")]
+    MessageMismatch,
+    #[msg("Signed approval has expired")]
+    ApprovalExpired,
+}
+
+#[error_code(offset = 6500)]
+pub enum WhiteListError {
+    #[msg("Only the authority can change the white list")]
+    Unauthorized,
+    #[msg("The token account is already whitelisted")]
+    AlreadyWhitelisted,
+}
+
+#[error_code(offset = 6600)]
+pub enum FeeError {
+    #[msg("A fee config account is required to enable transfer fees")]
+    MissingFeeConfig,
+    #[msg("Transfer fee basis points cannot exceed 10000")]
+    InvalidBasisPoints,
+    #[msg("Not enough harvested fees in the vault")]
+    InsufficientFees,
+    #[msg("Math operation overflow")]
+    MathOverflow,
+    #[msg("A fee config account can only be passed along with a transfer fee")]
+    UnexpectedFeeConfig,
+}
+
+#[error_code(offset = 6700)]
+pub enum RegistryError {
+    #[msg("Only the registry admin can do this")]
+    Unauthorized,
+    #[msg("Invalid security status transition")]
+    InvalidStatusTransition,
+    #[msg("The security is not active")]
+    NotActive,
+    #[msg("The registry already has a config")]
+    ConfigAlreadySet,
+    #[msg("The token account is not frozen")]
+    AccountNotFrozen,
+    #[msg("The token account is already frozen")]
+    AccountAlreadyFrozen,
+}
+
+#[error_code(offset = 6800)]
+pub enum MarketError {
+    #[msg("Amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("Price must be greater than zero")]
+    ZeroPrice,
+    #[msg("Too many investor classes")]
+    TooManyClasses,
+    #[msg("The market is still in its lockup period")]
+    Locked,
+    #[msg("Investor class is not allowed to trade on this market")]
+    ClassNotAllowed,
+    #[msg("Only whitelisted or attested holders can trade")]
+    HolderNotAllowed,
+    #[msg("Fill amount exceeds the open amount of the order")]
+    Overfill,
+    #[msg("The order still holds escrowed funds")]
+    OrderNotEmpty,
+    #[msg("Expected a matching transfer_checked of the security right after this instruction")]
+    MissingSettlementTransfer,
+    #[msg("Math operation overflow")]
+    MathOverflow,
+    #[msg("Market settlement instructions cannot be called by CPI")]
+    NotTopLevel,
+}
+
+#[error_code(offset = 6900)]
+pub enum BondError {
+    #[msg("Face value must be greater than zero")]
+    ZeroFaceValue,
+    #[msg("Maturity must be a whole number of coupon periods after the start")]
+    InvalidSchedule,
+    #[msg("The bond has not matured yet")]
+    NotMatured,
+    #[msg("No coupon period has elapsed since the last accrual")]
+    NothingToAccrue,
+    #[msg("No coupons to claim")]
+    NothingToClaim,
+    #[msg("Nothing to redeem")]
+    NothingToRedeem,
+    #[msg("Math operation overflow")]
+    MathOverflow,
+    #[msg("Coupons of elapsed periods must be accrued first")]
+    PendingAccrual,
+}
+
+#[error_code(offset = 7000)]
+pub enum RuleModuleError {
+    #[msg("Too many rule modules")]
+    TooManyModules,
+    #[msg("Hook account does not match the configured rule module program")]
+    ProgramMismatch,
+    #[msg("Not enough hook accounts for the configured rule modules")]
+    MissingAccounts,
+}
+
+#[error_code(offset = 7100)]
+pub enum HoldingLimitError {
+    #[msg("Basis points must be at most 10000")]
+    InvalidBasisPoints,
+    #[msg("Too many exempt accounts")]
+    TooManyExemptions,
+    #[msg("The receiving account would exceed the holding limits of this security")]
+    LimitExceeded,
+    #[msg("A supply share limit needs a nonzero supply basis")]
+    MissingSupplyBasis,
+}
+
+#[error_code(offset = 7200)]
+pub enum DocumentError {
+    #[msg("Document name cannot be empty")]
+    EmptyName,
+    #[msg("Document name is too long")]
+    NameTooLong,
+    #[msg("Document URI is too long")]
+    UriTooLong,
+    #[msg("No document in force with this name")]
+    UnknownDocument,
+    #[msg("The document history is full")]
+    TooManyVersions,
+    #[msg("The version in force of this document must be passed")]
+    NotCurrentVersion,
+}
+
+#[error_code(offset = 7300)]
+pub enum FundError {
+    #[msg("Amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("NAV must be greater than zero")]
+    ZeroNav,
+    #[msg("Only the NAV publisher can strike the NAV")]
+    NotNavPublisher,
+    #[msg("Only whitelisted or attested investors can subscribe")]
+    HolderNotAllowed,
+    #[msg("Not enough unreserved liquidity in the fund vault")]
+    InsufficientLiquidity,
+    #[msg("Math operation overflow")]
+    MathOverflow,
+    #[msg("The NAV of the request's epoch has already been struck")]
+    AlreadyStruck,
+}
+
+#[error_code(offset = 7400)]
+pub enum MemoError {
+    #[msg("Too many reason codes")]
+    TooManyReasonCodes,
+    #[msg("Reason codes must be non-empty, short and cannot contain the separator")]
+    InvalidReasonCode,
+    #[msg("Transfers of this security must be preceded by a memo with a valid reason code")]
+    MissingReasonMemo,
 }
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use crate::state::{ audit_log::*, config::Config, registry::Registry, vesting::* };
+use crate::errors::{ RegistryError, VestingError };
+
+pub fn achieve_milestone(ctx: Context<AchieveMilestone>, index: u8, reason_hash: [u8; 32]) -> Result<()> {
+    require!(ctx.accounts.registry.status.allows_changes(), RegistryError::NotActive);
+
+    let vesting = &mut ctx.accounts.vesting;
+    require!(!vesting.revoked, VestingError::Revoked);
+
+    let VestingSchedule::Milestone { milestones } = &mut vesting.schedule else {
+        return err!(VestingError::NotMilestoneSchedule);
+    };
+    let milestone = milestones.get_mut(index as usize).ok_or(VestingError::InvalidMilestone)?;
+    require!(!milestone.achieved, VestingError::MilestoneAlreadyAchieved);
+    milestone.achieved = true;
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.issuer.key(),
+        AuditAction::MilestoneAchieved,
+        ctx.accounts.vesting.key(),
+        reason_hash
+    )?;
+
+    msg!("Milestone {0} achieved", index);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AchieveMilestone<'info> {
+    pub issuer: Signer<'info>,
+
+    #[account(
+        has_one = config,
+        seeds = [b"registry", vesting.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        has_one = issuer,
+        seeds = [
+            b"vesting",
+            vesting.mint.as_ref(),
+            vesting.beneficiary.as_ref(),
+            vesting.seed.to_le_bytes().as_ref(),
+        ],
+        bump = vesting.bump
+    )]
+    pub vesting: Box<Account<'info, Vesting>>,
+
+    #[account(mut, seeds = [b"audit_log", vesting.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
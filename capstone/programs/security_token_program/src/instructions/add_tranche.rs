@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use crate::state::{ audit_log::*, config::*, registry::Registry };
+use crate::errors::SaleError;
+
+pub fn add_tranche(
+    ctx: Context<AddTranche>,
+    price: u64,
+    cap: u64,
+    start_ts: i64,
+    end_ts: i64,
+    whitelist_class: Option<u8>,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    require!(config.tranches.len() < MAX_TRANCHES, SaleError::TooManyTranches);
+    require!(start_ts < end_ts, SaleError::InvalidTrancheWindow);
+
+    config.tranches.push(Tranche {
+        price,
+        cap,
+        sold: 0,
+        start_ts,
+        end_ts,
+        whitelist_class,
+    });
+    msg!("Tranche {0} added", config.tranches.len() - 1);
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.owner.key(),
+        AuditAction::TrancheAdd,
+        ctx.accounts.config.key(),
+        reason_hash
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AddTranche<'info> {
+    #[account(
+        has_one = config,
+        seeds = [b"registry", config.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    #[account(mut, has_one = owner)]
+    pub config: Box<Account<'info, Config>>,
+    pub owner: Signer<'info>,
+
+    #[account(mut, seeds = [b"audit_log", config.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ self, FreezeAccount, Mint, ThawAccount, Token2022, TokenAccount };
+use crate::state::{ audit_log::*, registry::Registry };
+use crate::errors::RegistryError;
+
+// Thaws an investor's token account on a default-frozen mint, which admits it
+// as a holder in place of a white list entry
+pub fn approve_holder(ctx: Context<ApproveHolder>, reason_hash: [u8; 32]) -> Result<()> {
+    require!(ctx.accounts.token_account.is_frozen(), RegistryError::AccountNotFrozen);
+
+    thaw_if_frozen(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.token_account,
+        &ctx.accounts.mint.to_account_info(),
+        &ctx.accounts.freeze_authority.to_account_info(),
+        ctx.bumps.freeze_authority
+    )?;
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::HolderApproved,
+        ctx.accounts.token_account.key(),
+        reason_hash
+    )?;
+
+    msg!("Holder approved {0}", ctx.accounts.token_account.key());
+    Ok(())
+}
+
+// Freezes an approved holder's token account again, withdrawing the approval
+pub fn revoke_holder(ctx: Context<RevokeHolder>, reason_hash: [u8; 32]) -> Result<()> {
+    require!(!ctx.accounts.token_account.is_frozen(), RegistryError::AccountAlreadyFrozen);
+
+    let mint_key = ctx.accounts.mint.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[b"freeze_authority", mint_key.as_ref(), &[ctx.bumps.freeze_authority]]];
+    let cpi_accounts = FreezeAccount {
+        account: ctx.accounts.token_account.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        authority: ctx.accounts.freeze_authority.to_account_info(),
+    };
+    token_interface::freeze_account(
+        CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer_seeds)
+    )?;
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::HolderRevoked,
+        ctx.accounts.token_account.key(),
+        reason_hash
+    )?;
+
+    msg!("Holder revoked {0}", ctx.accounts.token_account.key());
+    Ok(())
+}
+
+// Accounts created by the program for its own escrows start frozen on a
+// default-frozen mint and are thawed as they are created
+pub fn thaw_if_frozen<'info>(
+    token_program: &AccountInfo<'info>,
+    token_account: &InterfaceAccount<'info, TokenAccount>,
+    mint: &AccountInfo<'info>,
+    freeze_authority: &AccountInfo<'info>,
+    freeze_authority_bump: u8
+) -> Result<()> {
+    if !token_account.is_frozen() {
+        return Ok(());
+    }
+
+    let mint_key = mint.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[b"freeze_authority", mint_key.as_ref(), &[freeze_authority_bump]]];
+    let cpi_accounts = ThawAccount {
+        account: token_account.to_account_info(),
+        mint: mint.clone(),
+        authority: freeze_authority.clone(),
+    };
+    token_interface::thaw_account(CpiContext::new_with_signer(token_program.clone(), cpi_accounts, signer_seeds))
+}
+
+#[derive(Accounts)]
+pub struct ApproveHolder<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ RegistryError::Unauthorized,
+        has_one = mint,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, token::mint = mint)]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Freeze authority of default-frozen mints, signs the thaw
+    #[account(seeds = [b"freeze_authority", mint.key().as_ref()], bump)]
+    pub freeze_authority: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"audit_log", mint.key().as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeHolder<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ RegistryError::Unauthorized,
+        has_one = mint,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, token::mint = mint)]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Freeze authority of default-frozen mints, signs the freeze
+    #[account(seeds = [b"freeze_authority", mint.key().as_ref()], bump)]
+    pub freeze_authority: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"audit_log", mint.key().as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub token_program: Program<'info, Token2022>,
+}
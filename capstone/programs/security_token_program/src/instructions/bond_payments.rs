@@ -0,0 +1,343 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        self,
+        Burn,
+        Mint,
+        Token2022,
+        TokenAccount,
+        TokenInterface,
+        TransferChecked,
+    },
+};
+use crate::state::{ audit_log::*, bond::*, registry::* };
+use crate::errors::{ BondError, RegistryError };
+use crate::TransferHook;
+
+// Bond terms for the mint. Coupons and redemptions are paid from the coupon
+// vault, which the issuer funds with plain transfers of the payment mint.
+pub fn create_bond(
+    ctx: Context<CreateBond>,
+    face_value: u64,
+    coupon_rate_bps: u16,
+    coupon_interval: i64,
+    start_ts: i64,
+    maturity_ts: i64,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    require!(face_value > 0, BondError::ZeroFaceValue);
+    require!(
+        coupon_interval > 0 &&
+            maturity_ts > start_ts &&
+            (maturity_ts - start_ts) % coupon_interval == 0,
+        BondError::InvalidSchedule
+    );
+
+    let bond = Bond {
+        mint: ctx.accounts.mint.key(),
+        payment_mint: ctx.accounts.payment_mint.key(),
+        face_value,
+        coupon_rate_bps,
+        coupon_interval,
+        start_ts,
+        maturity_ts,
+        periods_accrued: 0,
+        coupon_index: 0,
+        bump: ctx.bumps.bond,
+    };
+    bond.coupon_per_period().ok_or(BondError::MathOverflow)?;
+
+    msg!("Bond created: {0} coupons until {1}", bond.total_periods(), maturity_ts);
+    ctx.accounts.bond.set_inner(bond);
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::BondCreate,
+        ctx.accounts.bond.key(),
+        reason_hash
+    )?;
+    Ok(())
+}
+
+// Permissionless: moves the coupon index over every period elapsed since the
+// last accrual. The accrual covering the last period also matures the security.
+pub fn accrue_coupon(ctx: Context<AccrueCoupon>) -> Result<()> {
+    let bond = &mut ctx.accounts.bond;
+    let periods = bond.elapsed_periods(Clock::get()?.unix_timestamp);
+    require!(periods > bond.periods_accrued, BondError::NothingToAccrue);
+
+    let accrued = bond
+        .coupon_per_period()
+        .and_then(|coupon| coupon.checked_mul((periods - bond.periods_accrued) as u128))
+        .and_then(|coupon| bond.coupon_index.checked_add(coupon))
+        .ok_or(BondError::MathOverflow)?;
+    bond.coupon_index = accrued;
+    bond.periods_accrued = periods;
+    msg!("Coupon index {0} after {1} periods", bond.coupon_index, periods);
+
+    let registry = &mut ctx.accounts.registry;
+    if periods == bond.total_periods() && registry.status.can_transition_to(SecurityStatus::Matured) {
+        registry.status = SecurityStatus::Matured;
+        msg!("Security matured");
+    }
+    Ok(())
+}
+
+// Opts a token account into coupons from the current index onwards
+pub fn register_bond_holding(ctx: Context<RegisterBondHolding>) -> Result<()> {
+    ctx.accounts.holding.set_inner(BondHolding {
+        token_account: ctx.accounts.token_account.key(),
+        balance: ctx.accounts.token_account.amount,
+        index_checkpoint: ctx.accounts.bond.coupon_index,
+        accrued: 0,
+        bump: ctx.bumps.holding,
+    });
+    Ok(())
+}
+
+pub fn claim_coupon(ctx: Context<ClaimCoupon>) -> Result<()> {
+    let holding = &mut ctx.accounts.holding;
+    holding
+        .sync(ctx.accounts.bond.coupon_index, ctx.accounts.token_account.amount)
+        .ok_or(BondError::MathOverflow)?;
+    let amount = holding.accrued;
+    require!(amount > 0, BondError::NothingToClaim);
+    holding.accrued = 0;
+
+    ctx.accounts.pay(amount)?;
+    msg!("Coupon paid: {0}", amount);
+    Ok(())
+}
+
+// Burns the whole balance of the holder at maturity and pays its face value.
+// Burning does not run the hook, so unlike transfers it can be done by CPI.
+pub fn redeem_bond(ctx: Context<RedeemBond>) -> Result<()> {
+    require!(
+        Clock::get()?.unix_timestamp >= ctx.accounts.bond.maturity_ts,
+        BondError::NotMatured
+    );
+    // The holding is emptied below, so every coupon must be in the index first
+    require!(
+        ctx.accounts.bond.periods_accrued == ctx.accounts.bond.total_periods(),
+        BondError::PendingAccrual
+    );
+    let amount = ctx.accounts.token_account.amount;
+    require!(amount > 0, BondError::NothingToRedeem);
+    let payout = ctx.accounts.bond.face_value.checked_mul(amount).ok_or(BondError::MathOverflow)?;
+
+    let cpi_accounts = Burn {
+        mint: ctx.accounts.mint.to_account_info(),
+        from: ctx.accounts.token_account.to_account_info(),
+        authority: ctx.accounts.holder.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    token_interface::burn(cpi_ctx, amount)?;
+    sync_bond_holding(&ctx.accounts.bond.to_account_info(), &ctx.accounts.holding, 0)?;
+
+    let mint_key = ctx.accounts.mint.key();
+    let signer_seeds: [&[&[u8]]; 1] = [&[b"bond", mint_key.as_ref(), &[ctx.accounts.bond.bump]]];
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.coupon_vault.to_account_info(),
+        mint: ctx.accounts.payment_mint.to_account_info(),
+        to: ctx.accounts.holder_payment_account.to_account_info(),
+        authority: ctx.accounts.bond.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.payment_token_program.to_account_info(),
+        cpi_accounts,
+        &signer_seeds
+    );
+    token_interface::transfer_checked(cpi_ctx, payout, ctx.accounts.payment_mint.decimals)?;
+
+    msg!("Redeemed {0} tokens for {1}", amount, payout);
+    Ok(())
+}
+
+impl<'info> ClaimCoupon<'info> {
+    fn pay(&self, amount: u64) -> Result<()> {
+        let mint_key = self.mint.key();
+        let signer_seeds: [&[&[u8]]; 1] = [&[b"bond", mint_key.as_ref(), &[self.bond.bump]]];
+        let cpi_accounts = TransferChecked {
+            from: self.coupon_vault.to_account_info(),
+            mint: self.payment_mint.to_account_info(),
+            to: self.holder_payment_account.to_account_info(),
+            authority: self.bond.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.payment_token_program.to_account_info(),
+            cpi_accounts,
+            &signer_seeds
+        );
+        token_interface::transfer_checked(cpi_ctx, amount, self.payment_mint.decimals)
+    }
+}
+
+// Called by the hook after the balances moved. Mints without bond terms and
+// token accounts without a holding are skipped.
+impl<'info> TransferHook<'info> {
+    pub fn sync_bond_holdings(&self) -> Result<()> {
+        sync_bond_holding(&self.bond, &self.source_holding, self.source_token.amount)?;
+        sync_bond_holding(&self.bond, &self.destination_holding, self.destination_token.amount)
+    }
+}
+
+// Mints and burns do not run the hook, so the instructions that make them
+// refresh the holding of the token account with its balance afterwards
+pub fn sync_bond_holding(bond_info: &AccountInfo, holding_info: &AccountInfo, balance: u64) -> Result<()> {
+    if bond_info.owner != &crate::ID || bond_info.data_is_empty() {
+        return Ok(());
+    }
+    if holding_info.owner != &crate::ID || holding_info.data_is_empty() {
+        return Ok(());
+    }
+    let bond = Bond::try_deserialize(&mut &bond_info.data.borrow()[..])?;
+    let mut holding = BondHolding::try_deserialize(&mut &holding_info.data.borrow()[..])?;
+    holding.sync(bond.coupon_index, balance).ok_or(BondError::MathOverflow)?;
+    holding.try_serialize(&mut &mut holding_info.data.borrow_mut()[..])
+}
+
+#[derive(Accounts)]
+pub struct CreateBond<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ RegistryError::Unauthorized,
+        has_one = mint,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Bond::INIT_SPACE,
+        seeds = [b"bond", mint.key().as_ref()],
+        bump
+    )]
+    pub bond: Box<Account<'info, Bond>>,
+
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = payment_mint,
+        associated_token::authority = bond,
+        associated_token::token_program = payment_token_program
+    )]
+    pub coupon_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, seeds = [b"audit_log", mint.key().as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub payment_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AccrueCoupon<'info> {
+    #[account(mut, seeds = [b"bond", bond.mint.as_ref()], bump = bond.bump)]
+    pub bond: Box<Account<'info, Bond>>,
+
+    #[account(mut, seeds = [b"registry", bond.mint.as_ref()], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterBondHolding<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"bond", bond.mint.as_ref()], bump = bond.bump)]
+    pub bond: Box<Account<'info, Bond>>,
+
+    #[account(token::mint = bond.mint)]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + BondHolding::INIT_SPACE,
+        seeds = [b"bond_holding", token_account.key().as_ref()],
+        bump
+    )]
+    pub holding: Box<Account<'info, BondHolding>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCoupon<'info> {
+    pub holder: Signer<'info>,
+
+    #[account(has_one = mint, has_one = payment_mint, seeds = [b"bond", mint.key().as_ref()], bump = bond.bump)]
+    pub bond: Box<Account<'info, Bond>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(token::mint = mint, token::authority = holder)]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        has_one = token_account,
+        seeds = [b"bond_holding", token_account.key().as_ref()],
+        bump = holding.bump
+    )]
+    pub holding: Box<Account<'info, BondHolding>>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = bond,
+        associated_token::token_program = payment_token_program
+    )]
+    pub coupon_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = payment_mint)]
+    pub holder_payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub payment_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemBond<'info> {
+    pub holder: Signer<'info>,
+
+    #[account(has_one = mint, has_one = payment_mint, seeds = [b"bond", mint.key().as_ref()], bump = bond.bump)]
+    pub bond: Box<Account<'info, Bond>>,
+
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, token::mint = mint, token::authority = holder)]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: May be uninitialized, updated in `sync_bond_holding`
+    #[account(mut, seeds = [b"bond_holding", token_account.key().as_ref()], bump)]
+    pub holding: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = bond,
+        associated_token::token_program = payment_token_program
+    )]
+    pub coupon_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = payment_mint)]
+    pub holder_payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token2022>,
+    pub payment_token_program: Interface<'info, TokenInterface>,
+}
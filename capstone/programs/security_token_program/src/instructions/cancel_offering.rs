@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::{ audit_log::*, config::*, registry::Registry };
+use crate::errors::SaleError;
+
+pub fn cancel_offering(ctx: Context<CancelOffering>, reason_hash: [u8; 32]) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    require!(config.escrow_payments, SaleError::NotEscrowedSale);
+    require!(config.status == SaleStatus::Open, SaleError::SaleNotOpen);
+
+    config.status = SaleStatus::Cancelled;
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.owner.key(),
+        AuditAction::OfferingCancel,
+        ctx.accounts.config.key(),
+        reason_hash
+    )?;
+    msg!("Offering cancelled, subscribers can claim refunds");
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelOffering<'info> {
+    #[account(
+        has_one = config,
+        seeds = [b"registry", config.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    #[account(mut, has_one = owner)]
+    pub config: Box<Account<'info, Config>>,
+    pub owner: Signer<'info>,
+
+    #[account(mut, seeds = [b"audit_log", config.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
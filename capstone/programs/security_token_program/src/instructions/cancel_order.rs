@@ -0,0 +1,208 @@
+use anchor_lang::{ prelude::*, Discriminator };
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token_interface::{
+    self,
+    Approve,
+    CloseAccount,
+    Mint,
+    Token2022,
+    TokenAccount,
+    TokenInterface,
+    TransferChecked,
+};
+use crate::state::market::*;
+use crate::errors::MarketError;
+use crate::settlement::require_next_transfer;
+
+#[event]
+pub struct OrderCancelled {
+    pub order: Pubkey,
+    pub remaining: u64,
+}
+
+// Returns the escrow to the maker. Like a fill, the security of an ask comes back
+// through a `transfer_checked` right after this instruction, signed by the maker
+// as delegate of the vault.
+pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+    let order = &ctx.accounts.order;
+    let market_key = ctx.accounts.market.key();
+    let nonce = order.nonce.to_le_bytes();
+    let signer_seeds: [&[&[u8]]; 1] = [
+        &[b"order", market_key.as_ref(), order.maker.as_ref(), nonce.as_ref(), &[order.bump]],
+    ];
+
+    let security_balance = ctx.accounts.security_vault.amount;
+    if security_balance > 0 {
+        let cpi_accounts = Approve {
+            to: ctx.accounts.security_vault.to_account_info(),
+            delegate: ctx.accounts.maker.to_account_info(),
+            authority: ctx.accounts.order.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            &signer_seeds
+        );
+        token_interface::approve(cpi_ctx, security_balance)?;
+
+        require_next_transfer(
+            &ctx.accounts.instructions_sysvar,
+            &crate::instruction::CancelOrder::DISCRIMINATOR,
+            &ctx.accounts.security_vault.key(),
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.order.maker_token_account,
+            security_balance
+        )?;
+    }
+
+    let payment_balance = ctx.accounts.payment_vault.amount;
+    if payment_balance > 0 {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.payment_vault.to_account_info(),
+            mint: ctx.accounts.payment_mint.to_account_info(),
+            to: ctx.accounts.maker_payment_account.to_account_info(),
+            authority: ctx.accounts.order.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.payment_token_program.to_account_info(),
+            cpi_accounts,
+            &signer_seeds
+        );
+        token_interface::transfer_checked(cpi_ctx, payment_balance, ctx.accounts.payment_mint.decimals)?;
+    }
+
+    emit!(OrderCancelled {
+        order: ctx.accounts.order.key(),
+        remaining: ctx.accounts.order.remaining,
+    });
+    ctx.accounts.order.remaining = 0;
+    Ok(())
+}
+
+// Closes a filled or cancelled order once both vaults are empty, returning the rent
+pub fn close_order(ctx: Context<CloseOrder>) -> Result<()> {
+    require!(
+        ctx.accounts.order.remaining == 0 &&
+            ctx.accounts.security_vault.amount == 0 &&
+            ctx.accounts.payment_vault.amount == 0,
+        MarketError::OrderNotEmpty
+    );
+
+    let order = &ctx.accounts.order;
+    let market_key = order.market;
+    let nonce = order.nonce.to_le_bytes();
+    let signer_seeds: [&[&[u8]]; 1] = [
+        &[b"order", market_key.as_ref(), order.maker.as_ref(), nonce.as_ref(), &[order.bump]],
+    ];
+
+    let cpi_accounts = CloseAccount {
+        account: ctx.accounts.security_vault.to_account_info(),
+        destination: ctx.accounts.maker.to_account_info(),
+        authority: ctx.accounts.order.to_account_info(),
+    };
+    token_interface::close_account(
+        CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, &signer_seeds)
+    )?;
+
+    let cpi_accounts = CloseAccount {
+        account: ctx.accounts.payment_vault.to_account_info(),
+        destination: ctx.accounts.maker.to_account_info(),
+        authority: ctx.accounts.order.to_account_info(),
+    };
+    token_interface::close_account(
+        CpiContext::new_with_signer(
+            ctx.accounts.payment_token_program.to_account_info(),
+            cpi_accounts,
+            &signer_seeds
+        )
+    )?;
+
+    msg!("Order closed");
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    pub maker: Signer<'info>,
+
+    #[account(has_one = mint, has_one = payment_mint)]
+    pub market: Box<Account<'info, Market>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = market,
+        has_one = maker_payment_account,
+        seeds = [b"order", market.key().as_ref(), maker.key().as_ref(), order.nonce.to_le_bytes().as_ref()],
+        bump = order.bump
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = order,
+        associated_token::token_program = token_program
+    )]
+    pub security_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = order,
+        associated_token::token_program = payment_token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub maker_payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Instructions sysvar, used to check the return transfer of an ask
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token2022>,
+    pub payment_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CloseOrder<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"order", order.market.as_ref(), maker.key().as_ref(), order.nonce.to_le_bytes().as_ref()],
+        bump = order.bump
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = order,
+        associated_token::token_program = token_program
+    )]
+    pub security_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = order,
+        associated_token::token_program = payment_token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token2022>,
+    pub payment_token_program: Interface<'info, TokenInterface>,
+}
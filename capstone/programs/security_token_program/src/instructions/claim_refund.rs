@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{ self, Mint, TokenAccount, TokenInterface, TransferChecked },
+};
+use crate::state::{ config::*, registry::Registry, subscription::Subscription };
+use crate::errors::SaleError;
+
+pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(
+        config.status == SaleStatus::Failed ||
+            config.status == SaleStatus::Cancelled ||
+            ctx.accounts.subscription.refundable,
+        SaleError::RefundUnavailable
+    );
+
+    let amount = ctx.accounts.subscription.paid;
+    let mint_key = ctx.accounts.token_mint.key();
+    let signer_seeds: [&[&[u8]]; 1] = [&[b"config", mint_key.as_ref(), &[config.bump]]];
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.payment_vault.to_account_info(),
+        mint: ctx.accounts.payment_mint.to_account_info(),
+        to: ctx.accounts.payment_destination.to_account_info(),
+        authority: ctx.accounts.config.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.payment_token_program.to_account_info(),
+        cpi_accounts,
+        &signer_seeds
+    );
+    token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.payment_mint.decimals)?;
+
+    msg!("Refunded {0} to {1}", amount, ctx.accounts.investor.key());
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(mut)]
+    pub investor: Signer<'info>,
+
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        has_one = config,
+        seeds = [b"registry", token_mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    #[account(has_one = payment_mint)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        close = investor,
+        has_one = investor,
+        has_one = config,
+        seeds = [b"subscription", config.key().as_ref(), investor.key().as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Box<Account<'info, Subscription>>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = config,
+        associated_token::token_program = payment_token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = payment_mint)]
+    pub payment_destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub payment_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{ Mint, TokenAccount, TokenInterface },
+};
+use crate::state::{ audit_log::*, config::*, registry::Registry };
+use crate::errors::SaleError;
+
+pub fn configure_escrow(
+    ctx: Context<ConfigureEscrow>,
+    soft_cap: u64,
+    close_ts: i64,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    require!(config.total_sold == 0, SaleError::SaleAlreadyStarted);
+    require!(config.status == SaleStatus::Open, SaleError::SaleNotOpen);
+
+    // Subscriptions are taken until `close_ts`, so it cannot cut short a tranche already open
+    let now = Clock::get()?.unix_timestamp;
+    require!(close_ts > now, SaleError::InvalidCloseTime);
+    require!(
+        config.tranches
+            .iter()
+            .filter(|tranche| tranche.is_open(now))
+            .all(|tranche| close_ts >= tranche.end_ts),
+        SaleError::InvalidCloseTime
+    );
+
+    config.escrow_payments = true;
+    config.payment_mint = ctx.accounts.payment_mint.key();
+    config.soft_cap = soft_cap;
+    config.close_ts = close_ts;
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.owner.key(),
+        AuditAction::EscrowConfigUpdate,
+        ctx.accounts.config.key(),
+        reason_hash
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ConfigureEscrow<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        has_one = config,
+        seeds = [b"registry", config.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    #[account(mut, has_one = owner)]
+    pub config: Box<Account<'info, Config>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // Vault holding subscription payments until the offering closes
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = payment_mint,
+        associated_token::authority = config,
+        associated_token::token_program = payment_token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, seeds = [b"audit_log", config.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub payment_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
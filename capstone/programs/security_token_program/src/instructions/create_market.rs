@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use crate::state::{ audit_log::*, market::*, registry::Registry };
+use crate::errors::{ MarketError, RegistryError };
+
+pub fn create_market(
+    ctx: Context<CreateMarket>,
+    lockup_until: i64,
+    allowed_classes: Vec<u8>,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    require!(allowed_classes.len() <= MAX_MARKET_CLASSES, MarketError::TooManyClasses);
+
+    ctx.accounts.market.set_inner(Market {
+        mint: ctx.accounts.mint.key(),
+        payment_mint: ctx.accounts.payment_mint.key(),
+        config: ctx.accounts.registry.config,
+        lockup_until,
+        allowed_classes,
+        bump: ctx.bumps.market,
+    });
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::MarketCreate,
+        ctx.accounts.market.key(),
+        reason_hash
+    )?;
+    msg!("Market opened for {0}, locked until {1}", ctx.accounts.mint.key(), lockup_until);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateMarket<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ RegistryError::Unauthorized,
+        has_one = mint,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Market::INIT_SPACE,
+        seeds = [b"market", mint.key().as_ref()],
+        bump
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(mut, seeds = [b"audit_log", mint.key().as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub system_program: Program<'info, System>,
+}
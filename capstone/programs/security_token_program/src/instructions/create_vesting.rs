@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{ self, Mint, MintTo, Token2022, TokenAccount },
+};
+use crate::state::{ config::Config, registry::Registry, vesting::* };
+use crate::errors::{ HoldingLimitError, SaleError, VestingError };
+use crate::compliance::exceeds_holding_limits;
+use crate::instructions::approve_holder::thaw_if_frozen;
+
+pub fn create_vesting(
+    ctx: Context<CreateVesting>,
+    seed: u64,
+    amount: u64,
+    schedule: VestingSchedule
+) -> Result<()> {
+    // Milestone schedules are funded by the sum of their milestones
+    let total_amount = match &schedule {
+        VestingSchedule::Cliff { .. } => amount,
+        VestingSchedule::Linear { start_ts, cliff_ts, end_ts } => {
+            require!(
+                start_ts <= cliff_ts && cliff_ts <= end_ts && start_ts < end_ts,
+                VestingError::InvalidSchedule
+            );
+            amount
+        }
+        VestingSchedule::Milestone { milestones } => {
+            require!(
+                !milestones.is_empty() && milestones.len() <= MAX_MILESTONES,
+                VestingError::InvalidSchedule
+            );
+            require!(milestones.iter().all(|m| !m.achieved), VestingError::InvalidSchedule);
+            milestones
+                .iter()
+                .try_fold(0u64, |sum, m| sum.checked_add(m.amount))
+                .ok_or(VestingError::MathOverflow)?
+        }
+    };
+    require!(total_amount > 0, VestingError::ZeroAmount);
+
+    // Grants are issued supply, so they count against the cap like sales do
+    let config = &mut ctx.accounts.config;
+    let total_sold = config.total_sold.checked_add(total_amount).ok_or(VestingError::MathOverflow)?;
+    require!(total_sold <= config.max_supply, SaleError::MaxSupplyExceeded);
+    config.total_sold = total_sold;
+
+    ctx.accounts.vesting.set_inner(Vesting {
+        issuer: ctx.accounts.owner.key(),
+        beneficiary: ctx.accounts.beneficiary.key(),
+        mint: ctx.accounts.mint.key(),
+        seed,
+        total_amount,
+        released: 0,
+        revoked: false,
+        schedule,
+        bump: ctx.bumps.vesting,
+    });
+
+    // Escrow the full grant in the vault owned by the vesting PDA
+    thaw_if_frozen(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.vault,
+        &ctx.accounts.mint.to_account_info(),
+        &ctx.accounts.freeze_authority.to_account_info(),
+        ctx.bumps.freeze_authority
+    )?;
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.mint.to_account_info(),
+        to: ctx.accounts.vault.to_account_info(),
+        authority: ctx.accounts.mint_authority.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    token_interface::mint_to(cpi_ctx, total_amount)?;
+
+    // The grant is held for the beneficiary, so it is limited like their own balance
+    let exceeds_limits = exceeds_holding_limits(
+        &ctx.accounts.holding_limits.to_account_info(),
+        &ctx.accounts.vault.key(),
+        &ctx.accounts.beneficiary.key(),
+        total_amount
+    )?;
+    require!(!exceeds_limits, HoldingLimitError::LimitExceeded);
+
+    msg!("Vesting created for {0}: {1} tokens", ctx.accounts.beneficiary.key(), total_amount);
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct CreateVesting<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        has_one = config,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    #[account(mut, has_one = owner)]
+    pub config: Box<Account<'info, Config>>,
+
+    pub mint_authority: Signer<'info>,
+
+    /// CHECK: Wallet that receives the vested tokens
+    pub beneficiary: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Vesting::INIT_SPACE,
+        seeds = [
+            b"vesting",
+            mint.key().as_ref(),
+            beneficiary.key().as_ref(),
+            seed.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub vesting: Box<Account<'info, Vesting>>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Freeze authority of default-frozen mints, thaws the vault
+    #[account(seeds = [b"freeze_authority", mint.key().as_ref()], bump)]
+    pub freeze_authority: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, read in `exceeds_holding_limits`
+    #[account(seeds = [b"holding_limits", mint.key().as_ref()], bump)]
+    pub holding_limits: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
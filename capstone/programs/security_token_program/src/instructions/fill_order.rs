@@ -0,0 +1,196 @@
+use anchor_lang::{ prelude::*, Discriminator };
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token_interface::{
+    self,
+    Approve,
+    Mint,
+    Token2022,
+    TokenAccount,
+    TokenInterface,
+    TransferChecked,
+};
+use crate::state::{
+    investor_class::InvestorClass,
+    market::*,
+    registry::{ Registry, SecurityStatus },
+};
+use crate::errors::{ MarketError, RegistryError };
+use crate::settlement::require_next_transfer;
+
+#[event]
+pub struct OrderFilled {
+    pub order: Pubkey,
+    pub taker: Pubkey,
+    pub amount: u64,
+    pub cost: u64,
+}
+
+// Settles `amount` of a resting order. The payment leg is moved here; the security
+// leg is the `transfer_checked` right after this instruction, so the hook checks
+// the buyer like any other transfer:
+// - ask: the taker is approved as delegate of the security vault and moves the
+//   tokens to their own account
+// - bid: the taker moves their tokens to the maker's account
+pub fn fill_order(ctx: Context<FillOrder>, amount: u64) -> Result<()> {
+    require!(amount > 0, MarketError::ZeroAmount);
+    require!(ctx.accounts.registry.status == SecurityStatus::Active, RegistryError::NotActive);
+
+    let market = &ctx.accounts.market;
+    require!(Clock::get()?.unix_timestamp >= market.lockup_until, MarketError::Locked);
+    require!(
+        market.allows_class(ctx.accounts.investor_class.as_ref().map(|c| c.class)),
+        MarketError::ClassNotAllowed
+    );
+
+    let order = &ctx.accounts.order;
+    require!(amount <= order.remaining, MarketError::Overfill);
+    let cost = order.cost(amount).ok_or(MarketError::MathOverflow)?;
+
+    let market_key = market.key();
+    let nonce = order.nonce.to_le_bytes();
+    let signer_seeds: [&[&[u8]]; 1] = [
+        &[b"order", market_key.as_ref(), order.maker.as_ref(), nonce.as_ref(), &[order.bump]],
+    ];
+
+    match order.side {
+        OrderSide::Ask => {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.taker_payment_account.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.maker_payment_account.to_account_info(),
+                authority: ctx.accounts.taker.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.payment_token_program.to_account_info(), cpi_accounts);
+            token_interface::transfer_checked(cpi_ctx, cost, ctx.accounts.payment_mint.decimals)?;
+
+            let cpi_accounts = Approve {
+                to: ctx.accounts.security_vault.to_account_info(),
+                delegate: ctx.accounts.taker.to_account_info(),
+                authority: ctx.accounts.order.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                &signer_seeds
+            );
+            token_interface::approve(cpi_ctx, amount)?;
+
+            require_next_transfer(
+                &ctx.accounts.instructions_sysvar,
+                &crate::instruction::FillOrder::DISCRIMINATOR,
+                &ctx.accounts.security_vault.key(),
+                &ctx.accounts.mint.key(),
+                &ctx.accounts.taker_token_account.key(),
+                amount
+            )?;
+        }
+        OrderSide::Bid => {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.payment_vault.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.taker_payment_account.to_account_info(),
+                authority: ctx.accounts.order.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.payment_token_program.to_account_info(),
+                cpi_accounts,
+                &signer_seeds
+            );
+            token_interface::transfer_checked(cpi_ctx, cost, ctx.accounts.payment_mint.decimals)?;
+
+            require_next_transfer(
+                &ctx.accounts.instructions_sysvar,
+                &crate::instruction::FillOrder::DISCRIMINATOR,
+                &ctx.accounts.taker_token_account.key(),
+                &ctx.accounts.mint.key(),
+                &ctx.accounts.order.maker_token_account,
+                amount
+            )?;
+        }
+    }
+
+    ctx.accounts.order.remaining -= amount;
+    emit!(OrderFilled {
+        order: ctx.accounts.order.key(),
+        taker: ctx.accounts.taker.key(),
+        amount,
+        cost,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FillOrder<'info> {
+    pub taker: Signer<'info>,
+
+    #[account(
+        has_one = mint,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    #[account(
+        has_one = mint,
+        has_one = payment_mint,
+        seeds = [b"market", mint.key().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        has_one = market,
+        seeds = [
+            b"order",
+            market.key().as_ref(),
+            order.maker.as_ref(),
+            order.nonce.to_le_bytes().as_ref(),
+        ],
+        bump = order.bump
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = order,
+        associated_token::token_program = token_program
+    )]
+    pub security_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = order,
+        associated_token::token_program = payment_token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = order.maker_payment_account)]
+    pub maker_payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Receives the security of an ask, source of the security for a bid
+    #[account(token::mint = mint, token::authority = taker)]
+    pub taker_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = payment_mint)]
+    pub taker_payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"investor_class", market.config.as_ref(), taker.key().as_ref()],
+        bump = investor_class.bump
+    )]
+    pub investor_class: Option<Box<Account<'info, InvestorClass>>>,
+
+    /// CHECK: Instructions sysvar, used to check the settlement transfer
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token2022>,
+    pub payment_token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,156 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{ self, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked },
+};
+use crate::state::{ config::*, registry::Registry, subscription::Subscription };
+use crate::errors::SaleError;
+use crate::compliance::exceeds_holding_limits;
+use crate::instructions::bond_payments::sync_bond_holding;
+
+// The first call after `close_ts` decides the outcome. On success every call
+// then settles the `(subscription, recipient_token_account, recipient_bond_holding)`
+// triples passed as remaining accounts, so large offerings can be settled over
+// several transactions: the tokens are minted and the escrowed payment moves to
+// the treasury. A subscription whose tokens would break the holding limits is
+// left in escrow for the investor to reclaim with `claim_refund`.
+pub fn finalize_offering<'info>(
+    ctx: Context<'_, '_, 'info, 'info, FinalizeOffering<'info>>
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    require!(config.escrow_payments, SaleError::NotEscrowedSale);
+
+    if config.status == SaleStatus::Open {
+        require!(Clock::get()?.unix_timestamp >= config.close_ts, SaleError::SaleStillOpen);
+        msg!("Offering finalized: {0} raised, soft cap {1}", config.total_raised, config.soft_cap);
+
+        if config.total_raised >= config.soft_cap {
+            config.status = SaleStatus::Succeeded;
+        } else {
+            config.status = SaleStatus::Failed;
+        }
+    }
+
+    if ctx.accounts.config.status != SaleStatus::Succeeded {
+        return Ok(());
+    }
+
+    let triples = ctx.remaining_accounts.chunks_exact(3);
+    require!(triples.remainder().is_empty(), SaleError::InvalidSubscription);
+    for triple in triples {
+        let mut subscription = Account::<Subscription>::try_from(&triple[0])?;
+        require_keys_eq!(
+            subscription.config,
+            ctx.accounts.config.key(),
+            SaleError::InvalidSubscription
+        );
+        require_keys_eq!(subscription.recipient, triple[1].key(), SaleError::InvalidSubscription);
+        require!(!subscription.settled && !subscription.refundable, SaleError::AlreadySettled);
+
+        let recipient = InterfaceAccount::<TokenAccount>::try_from(&triple[1])?;
+        let balance = recipient.amount.checked_add(subscription.tokens).ok_or(SaleError::MathOverflow)?;
+        let exceeds_limits = exceeds_holding_limits(
+            &ctx.accounts.holding_limits.to_account_info(),
+            &recipient.key(),
+            &recipient.owner,
+            balance
+        )?;
+        if exceeds_limits {
+            subscription.refundable = true;
+            subscription.exit(&crate::ID)?;
+            msg!("Holding limits exceeded, {0} refundable to {1}", subscription.paid, subscription.investor);
+            continue;
+        }
+
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.token_mint.to_account_info(),
+            to: triple[1].clone(),
+            authority: ctx.accounts.mint_authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token_interface::mint_to(cpi_ctx, subscription.tokens)?;
+        ctx.accounts.release_payment(subscription.paid)?;
+
+        let (bond_holding, _) = Pubkey::find_program_address(
+            &[b"bond_holding", recipient.key().as_ref()],
+            &crate::ID
+        );
+        require_keys_eq!(triple[2].key(), bond_holding, SaleError::InvalidSubscription);
+        sync_bond_holding(&ctx.accounts.bond, &triple[2], balance)?;
+
+        subscription.settled = true;
+        subscription.exit(&crate::ID)?;
+        msg!("Settled {0} tokens for {1}", subscription.tokens, subscription.investor);
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FinalizeOffering<'info> {
+    pub owner: Signer<'info>,
+
+    pub mint_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        has_one = config,
+        seeds = [b"registry", token_mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    #[account(mut, has_one = owner, has_one = payment_mint)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = config,
+        associated_token::token_program = payment_token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = payment_mint)]
+    pub treasury: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: May be uninitialized, read in `exceeds_holding_limits`
+    #[account(seeds = [b"holding_limits", token_mint.key().as_ref()], bump)]
+    pub holding_limits: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, read in `sync_bond_holding`
+    #[account(seeds = [b"bond", token_mint.key().as_ref()], bump)]
+    pub bond: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub payment_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> FinalizeOffering<'info> {
+    fn release_payment(&self, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let mint_key = self.token_mint.key();
+        let signer_seeds: [&[&[u8]]; 1] = [&[b"config", mint_key.as_ref(), &[self.config.bump]]];
+
+        let cpi_accounts = TransferChecked {
+            from: self.payment_vault.to_account_info(),
+            mint: self.payment_mint.to_account_info(),
+            to: self.treasury.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.payment_token_program.to_account_info(),
+            cpi_accounts,
+            &signer_seeds
+        );
+        token_interface::transfer_checked(cpi_ctx, amount, self.payment_mint.decimals)
+    }
+}
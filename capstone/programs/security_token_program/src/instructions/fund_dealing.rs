@@ -0,0 +1,867 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        self,
+        Approve,
+        Burn,
+        Mint,
+        MintTo,
+        Token2022,
+        TokenAccount,
+        TokenInterface,
+        TransferChecked,
+    },
+};
+use crate::state::{
+    attestation::KycProviders,
+    audit_log::*,
+    fund::*,
+    registry::{ Registry, SecurityStatus },
+    whitelist::WhiteList,
+};
+use crate::errors::{ FundError, HoldingLimitError, RegistryError };
+use crate::compliance::{ exceeds_holding_limits, is_allowed_holder, is_thawed_holder };
+use crate::instructions::bond_payments::sync_bond_holding;
+
+#[event]
+pub struct NavStruck {
+    pub fund: Pubkey,
+    pub epoch: u64,
+    pub nav: u64,
+    pub subscriptions: u64,
+    pub redemptions: u64,
+    pub redeemable: u64,
+}
+
+#[event]
+pub struct SubscriptionRefunded {
+    pub fund: Pubkey,
+    pub investor: Pubkey,
+    pub epoch: u64,
+    pub payment: u64,
+}
+
+#[event]
+pub struct FundRequestSettled {
+    pub fund: Pubkey,
+    pub investor: Pubkey,
+    pub epoch: u64,
+    pub kind: FundRequestKind,
+    pub shares: u64,
+    pub payment: u64,
+}
+
+// Dealing at NAV for the mint. The issuer adds redemption liquidity to the
+// payment vault with plain transfers of the payment mint.
+pub fn create_fund(ctx: Context<CreateFund>, nav_publisher: Pubkey) -> Result<()> {
+    ctx.accounts.fund.set_inner(Fund {
+        mint: ctx.accounts.mint.key(),
+        payment_mint: ctx.accounts.payment_mint.key(),
+        nav_publisher,
+        current_epoch: 0,
+        pending_subscriptions: 0,
+        pending_redemptions: 0,
+        reserved: 0,
+        bump: ctx.bumps.fund,
+    });
+    Ok(())
+}
+
+pub fn set_nav_publisher(
+    ctx: Context<SetNavPublisher>,
+    nav_publisher: Pubkey,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    ctx.accounts.fund.nav_publisher = nav_publisher;
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::NavPublisherChange,
+        nav_publisher,
+        reason_hash
+    )?;
+    msg!("NAV publisher set to {0}", nav_publisher);
+    Ok(())
+}
+
+// Closes the current epoch at `nav`. When the vault, net of what earlier
+// requests are still owed, cannot pay every redemption of the epoch, each one is
+// filled pro rata. The epoch's subscriptions are not liquidity yet: they stay
+// reserved until their shares are minted or they are refunded.
+pub fn strike_nav(ctx: Context<StrikeNav>, nav: u64, reason_hash: [u8; 32]) -> Result<()> {
+    require!(nav > 0, FundError::ZeroNav);
+
+    let fund = &mut ctx.accounts.fund;
+    let redemptions = fund.pending_redemptions;
+    let subscriptions = fund.pending_subscriptions;
+    let available = ctx.accounts.payment_vault.amount
+        .saturating_sub(fund.reserved)
+        .saturating_sub(subscriptions);
+    let (redeemable, reserved) = redemption_fill(redemptions, nav, available).ok_or(
+        FundError::MathOverflow
+    )?;
+
+    let epoch = fund.current_epoch;
+    ctx.accounts.nav_record.set_inner(NavRecord {
+        fund: fund.key(),
+        epoch,
+        nav,
+        struck_at: Clock::get()?.unix_timestamp,
+        subscriptions,
+        redemptions,
+        redeemable,
+        reserved,
+        unsettled: redemptions,
+        bump: ctx.bumps.nav_record,
+    });
+    fund.reserved = fund.reserved
+        .checked_add(reserved)
+        .and_then(|total| total.checked_add(subscriptions))
+        .ok_or(FundError::MathOverflow)?;
+    fund.pending_subscriptions = 0;
+    fund.pending_redemptions = 0;
+    fund.current_epoch += 1;
+
+    emit!(NavStruck {
+        fund: fund.key(),
+        epoch,
+        nav,
+        subscriptions,
+        redemptions,
+        redeemable,
+    });
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.nav_publisher.key(),
+        AuditAction::NavStrike,
+        ctx.accounts.nav_record.key(),
+        reason_hash
+    )?;
+    Ok(())
+}
+
+// Escrows `amount` of payment in the vault until the next strike. The shares are
+// only known once the NAV is struck, so this only turns away investors who are
+// already at their holding limits; settlement refunds anything that breaches them.
+pub fn request_subscription(ctx: Context<RequestSubscription>, amount: u64) -> Result<()> {
+    require!(amount > 0, FundError::ZeroAmount);
+    require!(ctx.accounts.registry.status == SecurityStatus::Active, RegistryError::NotActive);
+
+    let investor = ctx.accounts.investor.key();
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        is_allowed_holder(
+            &ctx.accounts.white_list,
+            &ctx.accounts.kyc_providers,
+            &ctx.accounts.attestation.to_account_info(),
+            &ctx.accounts.token_account.key(),
+            &investor,
+            now
+        )? || is_thawed_holder(&ctx.accounts.mint.to_account_info(), &ctx.accounts.token_account)?,
+        FundError::HolderNotAllowed
+    );
+    let exceeds_limits = exceeds_holding_limits(
+        &ctx.accounts.holding_limits.to_account_info(),
+        &ctx.accounts.token_account.key(),
+        &investor,
+        ctx.accounts.token_account.amount.saturating_add(1)
+    )?;
+    require!(!exceeds_limits, HoldingLimitError::LimitExceeded);
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.payment_account.to_account_info(),
+        mint: ctx.accounts.payment_mint.to_account_info(),
+        to: ctx.accounts.payment_vault.to_account_info(),
+        authority: ctx.accounts.investor.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.payment_token_program.to_account_info(), cpi_accounts);
+    token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.payment_mint.decimals)?;
+
+    let fund = &mut ctx.accounts.fund;
+    fund.pending_subscriptions = fund.pending_subscriptions
+        .checked_add(amount)
+        .ok_or(FundError::MathOverflow)?;
+    ctx.accounts.request.set_inner(FundRequest {
+        fund: fund.key(),
+        investor,
+        epoch: fund.current_epoch,
+        kind: FundRequestKind::Subscription,
+        amount,
+        token_account: ctx.accounts.token_account.key(),
+        payment_account: ctx.accounts.payment_account.key(),
+        bump: ctx.bumps.request,
+    });
+    Ok(())
+}
+
+// Returns the payment of a subscription whose epoch has not been struck yet
+pub fn cancel_subscription(ctx: Context<CancelSubscription>) -> Result<()> {
+    let request = &ctx.accounts.request;
+    let fund = &mut ctx.accounts.fund;
+    require!(request.epoch == fund.current_epoch, FundError::AlreadyStruck);
+    fund.pending_subscriptions = fund.pending_subscriptions
+        .checked_sub(request.amount)
+        .ok_or(FundError::MathOverflow)?;
+
+    pay_from_vault(
+        fund,
+        &ctx.accounts.payment_vault,
+        &ctx.accounts.payment_mint,
+        &ctx.accounts.payment_account,
+        &ctx.accounts.payment_token_program,
+        request.amount
+    )?;
+
+    emit!(SubscriptionRefunded {
+        fund: fund.key(),
+        investor: request.investor,
+        epoch: request.epoch,
+        payment: request.amount,
+    });
+    Ok(())
+}
+
+// The shares stay with the investor, delegated to the fund, which burns the
+// filled part at settlement. A pro-rated remainder stays delegated until the
+// investor revokes it.
+pub fn request_redemption(ctx: Context<RequestRedemption>, shares: u64) -> Result<()> {
+    require!(shares > 0, FundError::ZeroAmount);
+    require!(ctx.accounts.registry.status == SecurityStatus::Active, RegistryError::NotActive);
+
+    // Requests of earlier epochs may still be waiting on the same delegation
+    let fund_key = ctx.accounts.fund.key();
+    let token_account = &ctx.accounts.token_account;
+    let delegated = if Option::<Pubkey>::from(token_account.delegate) == Some(fund_key) {
+        token_account.delegated_amount
+    } else {
+        0
+    };
+    let cpi_accounts = Approve {
+        to: token_account.to_account_info(),
+        delegate: ctx.accounts.fund.to_account_info(),
+        authority: ctx.accounts.investor.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    token_interface::approve(cpi_ctx, delegated.checked_add(shares).ok_or(FundError::MathOverflow)?)?;
+
+    let fund = &mut ctx.accounts.fund;
+    fund.pending_redemptions = fund.pending_redemptions
+        .checked_add(shares)
+        .ok_or(FundError::MathOverflow)?;
+    ctx.accounts.request.set_inner(FundRequest {
+        fund: fund_key,
+        investor: ctx.accounts.investor.key(),
+        epoch: fund.current_epoch,
+        kind: FundRequestKind::Redemption,
+        amount: shares,
+        token_account: ctx.accounts.token_account.key(),
+        payment_account: ctx.accounts.payment_account.key(),
+        bump: ctx.bumps.request,
+    });
+    Ok(())
+}
+
+// Mints the shares bought at the struck NAV, signed by the mint authority. When
+// they would take the investor over the holding limits, the payment is refunded.
+pub fn settle_subscription(ctx: Context<SettleSubscription>) -> Result<()> {
+    let nav = ctx.accounts.nav_record.nav;
+    let payment = ctx.accounts.request.amount;
+    let shares = shares_for(payment, nav).ok_or(FundError::MathOverflow)?;
+
+    let fund = &mut ctx.accounts.fund;
+    fund.reserved = fund.reserved.saturating_sub(payment);
+
+    let balance = ctx.accounts.token_account.amount.checked_add(shares).ok_or(FundError::MathOverflow)?;
+    let exceeds_limits = exceeds_holding_limits(
+        &ctx.accounts.holding_limits.to_account_info(),
+        &ctx.accounts.token_account.key(),
+        &ctx.accounts.token_account.owner,
+        balance
+    )?;
+    if exceeds_limits {
+        pay_from_vault(
+            &ctx.accounts.fund,
+            &ctx.accounts.payment_vault,
+            &ctx.accounts.payment_mint,
+            &ctx.accounts.payment_account,
+            &ctx.accounts.payment_token_program,
+            payment
+        )?;
+        msg!("Holding limits exceeded, subscription refunded");
+        emit!(SubscriptionRefunded {
+            fund: ctx.accounts.fund.key(),
+            investor: ctx.accounts.request.investor,
+            epoch: ctx.accounts.request.epoch,
+            payment,
+        });
+        return Ok(());
+    }
+
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.mint.to_account_info(),
+        to: ctx.accounts.token_account.to_account_info(),
+        authority: ctx.accounts.mint_authority.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    token_interface::mint_to(cpi_ctx, shares)?;
+    sync_bond_holding(&ctx.accounts.bond, &ctx.accounts.bond_holding, balance)?;
+
+    emit!(FundRequestSettled {
+        fund: ctx.accounts.fund.key(),
+        investor: ctx.accounts.request.investor,
+        epoch: ctx.accounts.request.epoch,
+        kind: FundRequestKind::Subscription,
+        shares,
+        payment,
+    });
+    Ok(())
+}
+
+// Permissionless: burns the filled shares through the delegation and pays them
+// at the struck NAV. Shares moved away since the request are not paid.
+pub fn settle_redemption(ctx: Context<SettleRedemption>) -> Result<()> {
+    let nav_record = &mut ctx.accounts.nav_record;
+    let filled = nav_record.filled_shares(ctx.accounts.request.amount);
+
+    let token_account = &ctx.accounts.token_account;
+    let fund_key = ctx.accounts.fund.key();
+    let delegated = if Option::<Pubkey>::from(token_account.delegate) == Some(fund_key) {
+        token_account.delegated_amount.min(token_account.amount)
+    } else {
+        0
+    };
+    let shares = filled.min(delegated);
+    let payment = payment_for(shares, nav_record.nav).ok_or(FundError::MathOverflow)?;
+    let reservation = payment_for(filled, nav_record.nav).ok_or(FundError::MathOverflow)?;
+
+    // The last settlement of the strike releases what rounding left reserved
+    nav_record.unsettled = nav_record.unsettled.saturating_sub(ctx.accounts.request.amount);
+    let release = if nav_record.unsettled == 0 {
+        nav_record.reserved
+    } else {
+        reservation.min(nav_record.reserved)
+    };
+    nav_record.reserved -= release;
+
+    let fund = &mut ctx.accounts.fund;
+    fund.reserved = fund.reserved.saturating_sub(release);
+
+    let mint_key = ctx.accounts.mint.key();
+    let signer_seeds: [&[&[u8]]; 1] = [&[b"fund", mint_key.as_ref(), &[fund.bump]]];
+    if shares > 0 {
+        let cpi_accounts = Burn {
+            mint: ctx.accounts.mint.to_account_info(),
+            from: ctx.accounts.token_account.to_account_info(),
+            authority: ctx.accounts.fund.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            &signer_seeds
+        );
+        token_interface::burn(cpi_ctx, shares)?;
+
+        ctx.accounts.token_account.reload()?;
+        sync_bond_holding(&ctx.accounts.bond, &ctx.accounts.bond_holding, ctx.accounts.token_account.amount)?;
+    }
+    if payment > 0 {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.payment_vault.to_account_info(),
+            mint: ctx.accounts.payment_mint.to_account_info(),
+            to: ctx.accounts.payment_account.to_account_info(),
+            authority: ctx.accounts.fund.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.payment_token_program.to_account_info(),
+            cpi_accounts,
+            &signer_seeds
+        );
+        token_interface::transfer_checked(cpi_ctx, payment, ctx.accounts.payment_mint.decimals)?;
+    }
+
+    emit!(FundRequestSettled {
+        fund: fund_key,
+        investor: ctx.accounts.request.investor,
+        epoch: ctx.accounts.request.epoch,
+        kind: FundRequestKind::Redemption,
+        shares,
+        payment,
+    });
+    Ok(())
+}
+
+// Proceeds of settled subscriptions and surplus liquidity, never what struck
+// requests are owed nor the payments of requests waiting for the next strike
+pub fn withdraw_fund_cash(
+    ctx: Context<WithdrawFundCash>,
+    amount: u64,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    let fund = &ctx.accounts.fund;
+    let available = ctx.accounts.payment_vault.amount
+        .saturating_sub(fund.reserved)
+        .saturating_sub(fund.pending_subscriptions);
+    require!(amount <= available, FundError::InsufficientLiquidity);
+
+    let mint_key = ctx.accounts.mint.key();
+    let signer_seeds: [&[&[u8]]; 1] = [&[b"fund", mint_key.as_ref(), &[fund.bump]]];
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.payment_vault.to_account_info(),
+        mint: ctx.accounts.payment_mint.to_account_info(),
+        to: ctx.accounts.destination.to_account_info(),
+        authority: ctx.accounts.fund.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.payment_token_program.to_account_info(),
+        cpi_accounts,
+        &signer_seeds
+    );
+    token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.payment_mint.decimals)?;
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::FundCashWithdraw,
+        ctx.accounts.destination.key(),
+        reason_hash
+    )?;
+    Ok(())
+}
+
+// Pays `amount` out of the fund's payment vault, signed by the fund
+fn pay_from_vault<'info>(
+    fund: &Account<'info, Fund>,
+    payment_vault: &InterfaceAccount<'info, TokenAccount>,
+    payment_mint: &InterfaceAccount<'info, Mint>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    payment_token_program: &Interface<'info, TokenInterface>,
+    amount: u64
+) -> Result<()> {
+    let signer_seeds: [&[&[u8]]; 1] = [&[b"fund", fund.mint.as_ref(), &[fund.bump]]];
+    let cpi_accounts = TransferChecked {
+        from: payment_vault.to_account_info(),
+        mint: payment_mint.to_account_info(),
+        to: to.to_account_info(),
+        authority: fund.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        payment_token_program.to_account_info(),
+        cpi_accounts,
+        &signer_seeds
+    );
+    token_interface::transfer_checked(cpi_ctx, amount, payment_mint.decimals)
+}
+
+#[derive(Accounts)]
+pub struct CreateFund<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ RegistryError::Unauthorized,
+        has_one = mint,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Fund::INIT_SPACE,
+        seeds = [b"fund", mint.key().as_ref()],
+        bump
+    )]
+    pub fund: Box<Account<'info, Fund>>,
+
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = payment_mint,
+        associated_token::authority = fund,
+        associated_token::token_program = payment_token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub payment_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetNavPublisher<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ RegistryError::Unauthorized,
+        seeds = [b"registry", registry.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    #[account(mut, seeds = [b"fund", registry.mint.as_ref()], bump = fund.bump)]
+    pub fund: Box<Account<'info, Fund>>,
+
+    #[account(mut, seeds = [b"audit_log", registry.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+#[derive(Accounts)]
+pub struct StrikeNav<'info> {
+    #[account(mut)]
+    pub nav_publisher: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = nav_publisher @ FundError::NotNavPublisher,
+        seeds = [b"fund", fund.mint.as_ref()],
+        bump = fund.bump
+    )]
+    pub fund: Box<Account<'info, Fund>>,
+
+    #[account(
+        associated_token::mint = fund.payment_mint,
+        associated_token::authority = fund,
+        associated_token::token_program = payment_token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = nav_publisher,
+        space = 8 + NavRecord::INIT_SPACE,
+        seeds = [b"nav", fund.key().as_ref(), fund.current_epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub nav_record: Box<Account<'info, NavRecord>>,
+
+    #[account(mut, seeds = [b"audit_log", fund.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub payment_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestSubscription<'info> {
+    #[account(mut)]
+    pub investor: Signer<'info>,
+
+    #[account(
+        has_one = mint,
+        has_one = white_list,
+        has_one = kyc_providers,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        has_one = mint,
+        has_one = payment_mint,
+        seeds = [b"fund", mint.key().as_ref()],
+        bump = fund.bump
+    )]
+    pub fund: Box<Account<'info, Fund>>,
+
+    // Receives the shares at settlement
+    #[account(token::mint = mint, token::authority = investor)]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = payment_mint, token::authority = investor)]
+    pub payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = fund,
+        associated_token::token_program = payment_token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub white_list: Box<Account<'info, WhiteList>>,
+
+    pub kyc_providers: Box<Account<'info, KycProviders>>,
+
+    /// CHECK: May be uninitialized, validated in `is_allowed_holder`
+    #[account(seeds = [b"attestation", mint.key().as_ref(), investor.key().as_ref()], bump)]
+    pub attestation: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, read in `exceeds_holding_limits`
+    #[account(seeds = [b"holding_limits", mint.key().as_ref()], bump)]
+    pub holding_limits: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = investor,
+        space = 8 + FundRequest::INIT_SPACE,
+        seeds = [
+            b"subscription",
+            fund.key().as_ref(),
+            investor.key().as_ref(),
+            fund.current_epoch.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub request: Box<Account<'info, FundRequest>>,
+
+    pub payment_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSubscription<'info> {
+    #[account(mut)]
+    pub investor: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = payment_mint,
+        seeds = [b"fund", fund.mint.as_ref()],
+        bump = fund.bump
+    )]
+    pub fund: Box<Account<'info, Fund>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        close = investor,
+        has_one = fund,
+        has_one = investor,
+        has_one = payment_account,
+        constraint = request.kind == FundRequestKind::Subscription
+    )]
+    pub request: Box<Account<'info, FundRequest>>,
+
+    #[account(mut)]
+    pub payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = fund,
+        associated_token::token_program = payment_token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub payment_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RequestRedemption<'info> {
+    #[account(mut)]
+    pub investor: Signer<'info>,
+
+    #[account(has_one = mint, seeds = [b"registry", mint.key().as_ref()], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, has_one = mint, seeds = [b"fund", mint.key().as_ref()], bump = fund.bump)]
+    pub fund: Box<Account<'info, Fund>>,
+
+    #[account(mut, token::mint = mint, token::authority = investor)]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Receives the proceeds at settlement
+    #[account(token::mint = fund.payment_mint)]
+    pub payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = investor,
+        space = 8 + FundRequest::INIT_SPACE,
+        seeds = [
+            b"redemption",
+            fund.key().as_ref(),
+            investor.key().as_ref(),
+            fund.current_epoch.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub request: Box<Account<'info, FundRequest>>,
+
+    pub token_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleSubscription<'info> {
+    pub mint_authority: Signer<'info>,
+
+    /// CHECK: Receives the rent of the request
+    #[account(mut)]
+    pub investor: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = mint,
+        has_one = payment_mint,
+        seeds = [b"fund", mint.key().as_ref()],
+        bump = fund.bump
+    )]
+    pub fund: Box<Account<'info, Fund>>,
+
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        has_one = fund,
+        seeds = [b"nav", fund.key().as_ref(), request.epoch.to_le_bytes().as_ref()],
+        bump = nav_record.bump
+    )]
+    pub nav_record: Box<Account<'info, NavRecord>>,
+
+    #[account(
+        mut,
+        close = investor,
+        has_one = fund,
+        has_one = investor,
+        has_one = token_account,
+        has_one = payment_account,
+        constraint = request.kind == FundRequestKind::Subscription
+    )]
+    pub request: Box<Account<'info, FundRequest>>,
+
+    #[account(mut)]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Receives the refund when the shares would breach the holding limits
+    #[account(mut)]
+    pub payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = fund,
+        associated_token::token_program = payment_token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: May be uninitialized, read in `exceeds_holding_limits`
+    #[account(seeds = [b"holding_limits", mint.key().as_ref()], bump)]
+    pub holding_limits: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, read in `sync_bond_holding`
+    #[account(seeds = [b"bond", mint.key().as_ref()], bump)]
+    pub bond: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, updated in `sync_bond_holding`
+    #[account(mut, seeds = [b"bond_holding", token_account.key().as_ref()], bump)]
+    pub bond_holding: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token2022>,
+    pub payment_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SettleRedemption<'info> {
+    /// CHECK: Receives the rent of the request
+    #[account(mut)]
+    pub investor: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = mint,
+        has_one = payment_mint,
+        seeds = [b"fund", mint.key().as_ref()],
+        bump = fund.bump
+    )]
+    pub fund: Box<Account<'info, Fund>>,
+
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        has_one = fund,
+        seeds = [b"nav", fund.key().as_ref(), request.epoch.to_le_bytes().as_ref()],
+        bump = nav_record.bump
+    )]
+    pub nav_record: Box<Account<'info, NavRecord>>,
+
+    #[account(
+        mut,
+        close = investor,
+        has_one = fund,
+        has_one = investor,
+        has_one = token_account,
+        has_one = payment_account,
+        constraint = request.kind == FundRequestKind::Redemption
+    )]
+    pub request: Box<Account<'info, FundRequest>>,
+
+    #[account(mut)]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = fund,
+        associated_token::token_program = payment_token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: May be uninitialized, read in `sync_bond_holding`
+    #[account(seeds = [b"bond", mint.key().as_ref()], bump)]
+    pub bond: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, updated in `sync_bond_holding`
+    #[account(mut, seeds = [b"bond_holding", token_account.key().as_ref()], bump)]
+    pub bond_holding: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token2022>,
+    pub payment_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFundCash<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ RegistryError::Unauthorized,
+        has_one = mint,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(has_one = mint, has_one = payment_mint, seeds = [b"fund", mint.key().as_ref()], bump = fund.bump)]
+    pub fund: Box<Account<'info, Fund>>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = fund,
+        associated_token::token_program = payment_token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = payment_mint)]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, seeds = [b"audit_log", mint.key().as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub payment_token_program: Interface<'info, TokenInterface>,
+}
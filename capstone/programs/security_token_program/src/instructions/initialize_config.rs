@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use crate::state::{ audit_log::*, config::*, registry::Registry };
+use crate::errors::RegistryError;
+
+pub fn initialize_config(ctx: Context<InitializeConfig>, max_supply: u64, reason_hash: [u8; 32]) -> Result<()> {
+    let registry = &mut ctx.accounts.registry;
+    require_keys_eq!(registry.config, Pubkey::default(), RegistryError::ConfigAlreadySet);
+    registry.config = ctx.accounts.config.key();
+
+    ctx.accounts.config.set_inner(Config {
+        owner: ctx.accounts.owner.key(),
+        mint: ctx.accounts.token_mint.key(),
+        compliance_key: Pubkey::default(),
+        max_supply,
+        total_sold: 0,
+        tranches: Vec::new(),
+        escrow_payments: false,
+        payment_mint: Pubkey::default(),
+        soft_cap: 0,
+        close_ts: 0,
+        total_raised: 0,
+        status: SaleStatus::Open,
+        bump: ctx.bumps.config,
+    });
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.owner.key(),
+        AuditAction::ConfigInit,
+        ctx.accounts.config.key(),
+        reason_hash
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = registry.admin == owner.key() @ RegistryError::Unauthorized,
+        seeds = [b"registry", token_mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [b"config", token_mint.key().as_ref()],
+        bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut, seeds = [b"audit_log", token_mint.key().as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use crate::state::{ attestation::*, audit_log::*, registry::Registry };
+use crate::errors::AttestationError;
+
+pub fn issue_attestation(
+    ctx: Context<IssueAttestation>,
+    level: u8,
+    expires_at: i64,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(expires_at > now, AttestationError::InvalidExpiry);
+
+    // A provider may renew its own attestations, or replace one that no longer counts
+    let existing = &ctx.accounts.attestation;
+    let provider = ctx.accounts.provider.key();
+    require!(
+        existing.provider == Pubkey::default() ||
+            existing.provider == provider ||
+            !existing.is_valid(&existing.investor, &ctx.accounts.kyc_providers, now),
+        AttestationError::AttestedByOtherProvider
+    );
+
+    ctx.accounts.attestation.set_inner(Attestation {
+        investor: ctx.accounts.investor.key(),
+        mint: ctx.accounts.registry.mint,
+        provider: ctx.accounts.provider.key(),
+        level,
+        expires_at,
+        bump: ctx.bumps.attestation,
+    });
+    msg!("Attestation issued for {0} until {1}", ctx.accounts.investor.key(), expires_at);
+    ctx.accounts.audit_log.load_mut()?.append(
+        provider,
+        AuditAction::AttestationIssue,
+        ctx.accounts.investor.key(),
+        reason_hash
+    )?;
+    Ok(())
+}
+
+pub fn revoke_attestation(ctx: Context<RevokeAttestation>, reason_hash: [u8; 32]) -> Result<()> {
+    msg!("Attestation revoked");
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.provider.key(),
+        AuditAction::AttestationRevoke,
+        ctx.accounts.attestation.investor,
+        reason_hash
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct IssueAttestation<'info> {
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    // Providers are trusted per mint, and so are their attestations
+    #[account(
+        has_one = kyc_providers,
+        seeds = [b"registry", registry.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        constraint = kyc_providers.providers.contains(&provider.key()) @ AttestationError::UnknownProvider
+    )]
+    pub kyc_providers: Account<'info, KycProviders>,
+
+    /// CHECK: Investor wallet being attested
+    pub investor: UncheckedAccount<'info>,
+
+    // One attestation per investor and mint, re-issuing replaces the previous one
+    // once it has expired or its provider is no longer trusted
+    #[account(
+        init_if_needed,
+        payer = provider,
+        space = 8 + Attestation::INIT_SPACE,
+        seeds = [b"attestation", registry.mint.as_ref(), investor.key().as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(mut, seeds = [b"audit_log", registry.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAttestation<'info> {
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    #[account(
+        mut,
+        close = provider,
+        has_one = provider,
+        seeds = [b"attestation", attestation.mint.as_ref(), attestation.investor.as_ref()],
+        bump = attestation.bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(mut, seeds = [b"audit_log", attestation.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
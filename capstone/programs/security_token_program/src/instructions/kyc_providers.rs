@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use crate::state::{ attestation::*, audit_log::*, registry::Registry };
+use crate::errors::AttestationError;
+
+pub fn add_kyc_provider(
+    ctx: Context<UpdateKycProviders>,
+    provider: Pubkey,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    let kyc_providers = &mut ctx.accounts.kyc_providers;
+    require!(!kyc_providers.providers.contains(&provider), AttestationError::ProviderAlreadyTrusted);
+    require!(kyc_providers.providers.len() < MAX_KYC_PROVIDERS, AttestationError::TooManyProviders);
+
+    kyc_providers.providers.push(provider);
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.authority.key(),
+        AuditAction::KycProviderAdd,
+        provider,
+        reason_hash
+    )?;
+    msg!("KYC provider trusted! {0}", provider);
+    Ok(())
+}
+
+pub fn remove_kyc_provider(
+    ctx: Context<UpdateKycProviders>,
+    provider: Pubkey,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    let kyc_providers = &mut ctx.accounts.kyc_providers;
+    let index = kyc_providers.providers
+        .iter()
+        .position(|p| *p == provider)
+        .ok_or(AttestationError::UnknownProvider)?;
+
+    // Attestations issued by a removed provider stop being accepted by the hook
+    kyc_providers.providers.remove(index);
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.authority.key(),
+        AuditAction::KycProviderRemove,
+        provider,
+        reason_hash
+    )?;
+    msg!("KYC provider removed! {0}", provider);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateKycProviders<'info> {
+    #[account(
+        has_one = kyc_providers,
+        seeds = [b"registry", registry.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        has_one = authority @ AttestationError::Unauthorized
+    )]
+    pub kyc_providers: Account<'info, KycProviders>,
+    #[account(mut, seeds = [b"audit_log", registry.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    pub authority: Signer<'info>,
+}
@@ -1,6 +1,23 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer};
-use crate::state::config::Config;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token_interface::{
+    self,
+    Mint,
+    MintTo,
+    TokenAccount,
+    TokenInterface,
+    TransferChecked,
+};
+use crate::state::{
+    approval::SignedApproval,
+    config::Config,
+    investor_class::InvestorClass,
+    registry::{ Registry, SecurityStatus },
+};
+use crate::errors::{ ApprovalError, HoldingLimitError, RegistryError, SaleError };
+use crate::ed25519::verify_compliance_approval;
+use crate::compliance::exceeds_holding_limits;
+use crate::instructions::bond_payments::sync_bond_holding;
 
 #[derive(Accounts)]
 pub struct MintTokens<'info> {
@@ -8,54 +25,169 @@ pub struct MintTokens<'info> {
     pub payer: Signer<'info>,
 
     // Conta de token de pagamento do usuário
-    #[account(mut)]
-    pub payment_source: Box<Account<'info, TokenAccount>>,
+    #[account(mut, token::mint = payment_mint)]
+    pub payment_source: Box<InterfaceAccount<'info, TokenAccount>>,
 
     // Conta que receberá o pagamento (tesouraria)
-    #[account(mut)]
-    pub treasury: Box<Account<'info, TokenAccount>>,
+    #[account(mut, token::mint = payment_mint)]
+    pub treasury: Box<InterfaceAccount<'info, TokenAccount>>,
 
     // Mint do token que será emitido
     #[account(mut)]
-    pub token_mint: Box<Account<'info, Mint>>,
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
 
     // Conta destino dos tokens mintados
-    #[account(mut)]
-    pub recipient_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, token::mint = token_mint)]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     // Autoridade do mint (pode ser um PDA; se for o caso, use seeds e bump)
     pub mint_authority: Signer<'info>,
 
+    // Registro do token, de onde vem a configuração
+    #[account(
+        has_one = config,
+        seeds = [b"registry", token_mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
     // Configuração do programa
     #[account(mut, has_one = owner)]
     pub config: Box<Account<'info, Config>>,
 
+    /// CHECK: May be uninitialized, read in `exceeds_holding_limits`
+    #[account(seeds = [b"holding_limits", token_mint.key().as_ref()], bump)]
+    pub holding_limits: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, read in `sync_bond_holding`
+    #[account(seeds = [b"bond", token_mint.key().as_ref()], bump)]
+    pub bond: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, updated in `sync_bond_holding`
+    #[account(mut, seeds = [b"bond_holding", recipient_token_account.key().as_ref()], bump)]
+    pub bond_holding: UncheckedAccount<'info>,
+
     pub owner: Signer<'info>,
 
+    // Classe do investidor, necessária apenas para tranches restritas
+    #[account(
+        seeds = [b"investor_class", config.key().as_ref(), payer.key().as_ref()],
+        bump = investor_class.bump
+    )]
+    pub investor_class: Option<Box<Account<'info, InvestorClass>>>,
+
+    // Aprovação assinada (ed25519) pela chave de compliance, opcional
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + SignedApproval::INIT_SPACE,
+        seeds = [
+            b"approval",
+            token_mint.key().as_ref(),
+            recipient_token_account.owner.as_ref(),
+        ],
+        bump
+    )]
+    pub approval: Option<Box<Account<'info, SignedApproval>>>,
+
+    /// CHECK: Instructions sysvar, obrigatório quando `approval` é informado
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+
     // Mint do token de pagamento
-    pub payment_mint: Box<Account<'info, Mint>>,
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub payment_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct TrancheFill {
+    pub tranche: u8,
+    pub amount: u64,
+    pub cost: u64,
+}
+
+#[event]
+pub struct TokensPurchased {
+    pub buyer: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub cost: u64,
+    pub fills: Vec<TrancheFill>,
+}
+
+// Preenche as tranches abertas em ordem até cobrir `mint_amount`
+pub fn fill_tranches(
+    config: &mut Config,
+    mint_amount: u64,
+    investor_class: Option<u8>,
+    now: i64
+) -> Result<(u64, Vec<TrancheFill>)> {
+    require!(mint_amount > 0, SaleError::ZeroAmount);
+
+    let total_sold = config.total_sold
+        .checked_add(mint_amount)
+        .ok_or(SaleError::MathOverflow)?;
+    require!(total_sold <= config.max_supply, SaleError::MaxSupplyExceeded);
+
+    let mut remaining = mint_amount;
+    let mut cost: u64 = 0;
+    let mut fills = Vec::new();
+
+    for (index, tranche) in config.tranches.iter_mut().enumerate() {
+        if remaining == 0 {
+            break;
+        }
+        if !tranche.is_open(now) || !tranche.accepts(investor_class) {
+            continue;
+        }
+
+        let amount = remaining.min(tranche.remaining());
+        if amount == 0 {
+            continue;
+        }
+
+        let tranche_cost = tranche.price.checked_mul(amount).ok_or(SaleError::MathOverflow)?;
+        cost = cost.checked_add(tranche_cost).ok_or(SaleError::MathOverflow)?;
+        tranche.sold += amount;
+        remaining -= amount;
+
+        fills.push(TrancheFill {
+            tranche: index as u8,
+            amount,
+            cost: tranche_cost,
+        });
+    }
+
+    require!(remaining == 0, SaleError::InsufficientTrancheCapacity);
+    config.total_sold = total_sold;
+
+    Ok((cost, fills))
+}
+
     pub fn mint_tokens(ctx: Context<MintTokens>, mint_amount: u64) -> Result<()> {
-        
+
+        require!(ctx.accounts.registry.status == SecurityStatus::Active, RegistryError::NotActive);
+        require!(!ctx.accounts.config.escrow_payments, SaleError::EscrowedSale);
+
+        let investor_class = ctx.accounts.investor_class.as_ref().map(|c| c.class);
+        let now = Clock::get()?.unix_timestamp;
+
         // Verifica se o valor a ser mintado é menor ou igual ao máximo permitido
-        
-        
-        let cost = ctx.accounts.config.price.checked_mul(mint_amount)
-        .ok_or(ErrorCode::MathOverflow)?;
-    
+        let (cost, fills) = fill_tranches(&mut ctx.accounts.config, mint_amount, investor_class, now)?;
+
     // Transferência do token de pagamento
-    let cpi_accounts = Transfer {
+    let cpi_accounts = TransferChecked {
         from: ctx.accounts.payment_source.to_account_info(),
+        mint: ctx.accounts.payment_mint.to_account_info(),
         to: ctx.accounts.treasury.to_account_info(),
         authority: ctx.accounts.payer.to_account_info(),
     };
-    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
-    token::transfer(cpi_ctx, cost)?;
-    
+    let cpi_ctx = CpiContext::new(ctx.accounts.payment_token_program.to_account_info(), cpi_accounts);
+    token_interface::transfer_checked(cpi_ctx, cost, ctx.accounts.payment_mint.decimals)?;
+
     // Mint dos tokens
     let cpi_accounts = MintTo {
         mint: ctx.accounts.token_mint.to_account_info(),
@@ -63,13 +195,56 @@ pub struct MintTokens<'info> {
         authority: ctx.accounts.mint_authority.to_account_info(),
     };
     let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
-    token::mint_to(cpi_ctx, mint_amount)?;
-    
+    token_interface::mint_to(cpi_ctx, mint_amount)?;
+
+    // Limites de participação sobre o saldo após o mint
+    ctx.accounts.recipient_token_account.reload()?;
+    let exceeds_limits = exceeds_holding_limits(
+        &ctx.accounts.holding_limits.to_account_info(),
+        &ctx.accounts.recipient_token_account.key(),
+        &ctx.accounts.recipient_token_account.owner,
+        ctx.accounts.recipient_token_account.amount
+    )?;
+    require!(!exceeds_limits, HoldingLimitError::LimitExceeded);
+    sync_bond_holding(
+        &ctx.accounts.bond,
+        &ctx.accounts.bond_holding,
+        ctx.accounts.recipient_token_account.amount
+    )?;
+
+    // Guarda a aprovação para que transferências futuras passem pelo hook
+    if let Some(approval) = ctx.accounts.approval.as_mut() {
+        let instructions_sysvar = ctx.accounts.instructions_sysvar
+            .as_ref()
+            .ok_or(ApprovalError::MissingSignature)?;
+        let wallet = ctx.accounts.recipient_token_account.owner;
+        let mint = ctx.accounts.token_mint.key();
+        let expires_at = verify_compliance_approval(
+            instructions_sysvar,
+            &ctx.accounts.config.compliance_key,
+            &wallet,
+            &mint
+        )?;
+
+        approval.set_inner(SignedApproval {
+            wallet,
+            mint,
+            expires_at,
+            signer: ctx.accounts.config.compliance_key,
+            bump: ctx.bumps.approval.unwrap(),
+        });
+    }
+
+    for fill in fills.iter() {
+        msg!("Tranche {0}: {1} tokens for {2}", fill.tranche, fill.amount, fill.cost);
+    }
+    emit!(TokensPurchased {
+        buyer: ctx.accounts.payer.key(),
+        mint: ctx.accounts.token_mint.key(),
+        amount: mint_amount,
+        cost,
+        fills,
+    });
+
     Ok(())
 }
-
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Math operation overflow")]
-    MathOverflow,
-}
\ No newline at end of file
@@ -1,9 +1,108 @@
 // instructions/mod.rs
+pub mod achieve_milestone;
+pub use achieve_milestone::*;
+
+pub mod add_tranche;
+pub use add_tranche::*;
+
+pub mod approve_holder;
+pub use approve_holder::*;
+
+pub mod bond_payments;
+pub use bond_payments::*;
+
+pub mod cancel_offering;
+pub use cancel_offering::*;
+
+pub mod cancel_order;
+pub use cancel_order::*;
+
+pub mod claim_refund;
+pub use claim_refund::*;
+
+pub mod configure_escrow;
+pub use configure_escrow::*;
+
+pub mod create_market;
+pub use create_market::*;
+
+pub mod create_vesting;
+pub use create_vesting::*;
+
+pub mod fill_order;
+pub use fill_order::*;
+
+pub mod finalize_offering;
+pub use finalize_offering::*;
+
+pub mod fund_dealing;
+pub use fund_dealing::*;
+
+pub mod initialize_config;
+pub use initialize_config::*;
+
+pub mod issue_attestation;
+pub use issue_attestation::*;
+
+pub mod kyc_providers;
+pub use kyc_providers::*;
+
 pub mod  mint_tokens;
 pub use  mint_tokens::*;
 
+pub mod post_order;
+pub use post_order::*;
+
+pub mod release_vested;
+pub use release_vested::*;
+
+pub mod revoke_vesting;
+pub use revoke_vesting::*;
+
+pub mod set_compliance_key;
+pub use set_compliance_key::*;
+
+pub mod set_document;
+pub use set_document::*;
+
+pub mod set_holding_limits;
+pub use set_holding_limits::*;
+
+pub mod set_memo_policy;
+pub use set_memo_policy::*;
+
+pub mod set_investor_class;
+pub use set_investor_class::*;
+
+pub mod set_registry_status;
+pub use set_registry_status::*;
+
+pub mod set_rule_modules;
+pub use set_rule_modules::*;
+
+pub mod submit_approval;
+pub use submit_approval::*;
+
+pub mod subscribe;
+pub use subscribe::*;
+
+pub mod transfer_fees;
+pub use transfer_fees::*;
+
+pub mod update_extra_account_meta_list;
+pub use update_extra_account_meta_list::*;
+
 pub mod update_price;
 pub use update_price::*;
 
+pub mod update_security_metadata;
+pub use update_security_metadata::*;
+
 pub mod whitelist_add;
-pub use whitelist_add::*;
\ No newline at end of file
+pub use whitelist_add::*;
+
+pub mod whitelist_applications;
+pub use whitelist_applications::*;
+
+pub mod whitelist_batch;
+pub use whitelist_batch::*;
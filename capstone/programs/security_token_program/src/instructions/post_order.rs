@@ -0,0 +1,212 @@
+use anchor_lang::{ prelude::*, Discriminator };
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{ self, Mint, Token2022, TokenAccount, TokenInterface, TransferChecked },
+};
+use crate::state::{
+    attestation::KycProviders,
+    investor_class::InvestorClass,
+    market::*,
+    registry::{ Registry, SecurityStatus },
+    whitelist::WhiteList,
+};
+use crate::errors::{ MarketError, RegistryError };
+use crate::compliance::{ is_allowed_holder, is_thawed_holder };
+use crate::instructions::approve_holder::thaw_if_frozen;
+use crate::settlement::{ net_of_transfer_fee, require_next_transfer };
+
+#[event]
+pub struct OrderPosted {
+    pub order: Pubkey,
+    pub maker: Pubkey,
+    pub side: OrderSide,
+    pub price: u64,
+    pub amount: u64,
+}
+
+// Bids escrow their payment right away. Asks escrow the security with a
+// `transfer_checked` into the security vault placed right after this instruction.
+pub fn post_order(
+    ctx: Context<PostOrder>,
+    nonce: u64,
+    side: OrderSide,
+    price: u64,
+    amount: u64
+) -> Result<()> {
+    require!(amount > 0, MarketError::ZeroAmount);
+    require!(price > 0, MarketError::ZeroPrice);
+    require!(ctx.accounts.registry.status == SecurityStatus::Active, RegistryError::NotActive);
+
+    let now = Clock::get()?.unix_timestamp;
+    let market = &ctx.accounts.market;
+    require!(now >= market.lockup_until, MarketError::Locked);
+    require!(
+        market.allows_class(ctx.accounts.investor_class.as_ref().map(|c| c.class)),
+        MarketError::ClassNotAllowed
+    );
+
+    let maker = ctx.accounts.maker.key();
+    require!(
+        is_allowed_holder(
+            &ctx.accounts.white_list,
+            &ctx.accounts.kyc_providers,
+            &ctx.accounts.attestation.to_account_info(),
+            &ctx.accounts.maker_token_account.key(),
+            &maker,
+            now
+        )? || is_thawed_holder(&ctx.accounts.mint.to_account_info(), &ctx.accounts.maker_token_account)?,
+        MarketError::HolderNotAllowed
+    );
+
+    // An ask can only sell what reaches the vault once the transfer fee is withheld
+    let remaining = match side {
+        OrderSide::Bid => amount,
+        OrderSide::Ask => net_of_transfer_fee(&ctx.accounts.mint.to_account_info(), amount)?,
+    };
+    require!(remaining > 0, MarketError::ZeroAmount);
+
+    ctx.accounts.order.set_inner(Order {
+        market: market.key(),
+        maker,
+        nonce,
+        side,
+        price,
+        remaining,
+        maker_token_account: ctx.accounts.maker_token_account.key(),
+        maker_payment_account: ctx.accounts.maker_payment_account.key(),
+        bump: ctx.bumps.order,
+    });
+
+    thaw_if_frozen(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.security_vault,
+        &ctx.accounts.mint.to_account_info(),
+        &ctx.accounts.freeze_authority.to_account_info(),
+        ctx.bumps.freeze_authority
+    )?;
+
+    match side {
+        OrderSide::Bid => {
+            let cost = ctx.accounts.order.cost(amount).ok_or(MarketError::MathOverflow)?;
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.maker_payment_account.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.payment_vault.to_account_info(),
+                authority: ctx.accounts.maker.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.payment_token_program.to_account_info(), cpi_accounts);
+            token_interface::transfer_checked(cpi_ctx, cost, ctx.accounts.payment_mint.decimals)?;
+        }
+        OrderSide::Ask => {
+            // The hook lets the vault receive this transfer, see `is_order_escrow`
+            require_next_transfer(
+                &ctx.accounts.instructions_sysvar,
+                &crate::instruction::PostOrder::DISCRIMINATOR,
+                &ctx.accounts.maker_token_account.key(),
+                &ctx.accounts.mint.key(),
+                &ctx.accounts.security_vault.key(),
+                amount
+            )?;
+        }
+    }
+
+    emit!(OrderPosted {
+        order: ctx.accounts.order.key(),
+        maker,
+        side,
+        price,
+        amount: remaining,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64, side: OrderSide)]
+pub struct PostOrder<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        has_one = mint,
+        has_one = white_list,
+        has_one = kyc_providers,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    #[account(
+        has_one = mint,
+        has_one = payment_mint,
+        seeds = [b"market", mint.key().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // Source of an ask, receives the security of a bid
+    #[account(token::mint = mint, token::authority = maker)]
+    pub maker_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Source of a bid, receives the payment of an ask
+    #[account(mut, token::mint = payment_mint, token::authority = maker)]
+    pub maker_payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = maker,
+        space = 8 + Order::INIT_SPACE,
+        seeds = [b"order", market.key().as_ref(), maker.key().as_ref(), nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint,
+        associated_token::authority = order,
+        associated_token::token_program = token_program
+    )]
+    pub security_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = payment_mint,
+        associated_token::authority = order,
+        associated_token::token_program = payment_token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Freeze authority of default-frozen mints, thaws the security vault
+    #[account(seeds = [b"freeze_authority", mint.key().as_ref()], bump)]
+    pub freeze_authority: UncheckedAccount<'info>,
+
+    pub white_list: Box<Account<'info, WhiteList>>,
+
+    pub kyc_providers: Box<Account<'info, KycProviders>>,
+
+    /// CHECK: May be uninitialized, validated in `is_allowed_holder`
+    #[account(seeds = [b"attestation", mint.key().as_ref(), maker.key().as_ref()], bump)]
+    pub attestation: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"investor_class", market.config.as_ref(), maker.key().as_ref()],
+        bump = investor_class.bump
+    )]
+    pub investor_class: Option<Box<Account<'info, InvestorClass>>>,
+
+    /// CHECK: Instructions sysvar, used to check the escrow transfer of an ask
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token2022>,
+    pub payment_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
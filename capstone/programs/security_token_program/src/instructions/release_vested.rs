@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ self, Approve, Mint, Token2022, TokenAccount };
+use crate::state::vesting::Vesting;
+use crate::errors::VestingError;
+
+// The vault cannot be moved by a CPI from this program, because Token-2022 would
+// call back into this same program as the transfer hook (reentrancy is rejected
+// by the runtime). Instead the newly vested amount is delegated to the
+// beneficiary, who moves it with a regular `transfer_checked` that runs the hook.
+pub fn release_vested(ctx: Context<ReleaseVested>) -> Result<()> {
+    let vesting = &ctx.accounts.vesting;
+    let vested = vesting.vested_amount(Clock::get()?.unix_timestamp);
+    let claimable = vested.saturating_sub(vesting.released);
+    require!(claimable > 0, VestingError::NothingToRelease);
+
+    let allowance = ctx.accounts.vault.delegated_amount
+        .checked_add(claimable)
+        .ok_or(VestingError::MathOverflow)?;
+
+    let mint_key = ctx.accounts.mint.key();
+    let beneficiary_key = ctx.accounts.beneficiary.key();
+    let seed = vesting.seed.to_le_bytes();
+    let signer_seeds: [&[&[u8]]; 1] = [
+        &[b"vesting", mint_key.as_ref(), beneficiary_key.as_ref(), seed.as_ref(), &[vesting.bump]],
+    ];
+
+    let cpi_accounts = Approve {
+        to: ctx.accounts.vault.to_account_info(),
+        delegate: ctx.accounts.beneficiary.to_account_info(),
+        authority: ctx.accounts.vesting.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        &signer_seeds
+    );
+    token_interface::approve(cpi_ctx, allowance)?;
+
+    ctx.accounts.vesting.released = vested;
+    msg!("Released {0} vested tokens to {1}", claimable, beneficiary_key);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ReleaseVested<'info> {
+    pub beneficiary: Signer<'info>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        has_one = beneficiary,
+        has_one = mint,
+        seeds = [
+            b"vesting",
+            mint.key().as_ref(),
+            beneficiary.key().as_ref(),
+            vesting.seed.to_le_bytes().as_ref(),
+        ],
+        bump = vesting.bump
+    )]
+    pub vesting: Box<Account<'info, Vesting>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token2022>,
+}
@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ self, Burn, Mint, Token2022, TokenAccount };
+use crate::state::{ audit_log::*, config::Config, registry::Registry, vesting::Vesting };
+use crate::errors::VestingError;
+use crate::instructions::bond_payments::sync_bond_holding;
+
+// Unvested tokens are burned from the vault rather than transferred back, since
+// a transfer signed by this program would re-enter it through the hook.
+pub fn revoke_vesting(ctx: Context<RevokeVesting>, reason_hash: [u8; 32]) -> Result<()> {
+    let vesting = &ctx.accounts.vesting;
+    require!(!vesting.revoked, VestingError::Revoked);
+
+    let vested = vesting.vested_amount(Clock::get()?.unix_timestamp);
+    let unvested = vesting.total_amount - vested;
+
+    if unvested > 0 {
+        let mint_key = ctx.accounts.mint.key();
+        let beneficiary_key = vesting.beneficiary;
+        let seed = vesting.seed.to_le_bytes();
+        let signer_seeds: [&[&[u8]]; 1] = [
+            &[b"vesting", mint_key.as_ref(), beneficiary_key.as_ref(), seed.as_ref(), &[vesting.bump]],
+        ];
+
+        let cpi_accounts = Burn {
+            mint: ctx.accounts.mint.to_account_info(),
+            from: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.vesting.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            &signer_seeds
+        );
+        token_interface::burn(cpi_ctx, unvested)?;
+
+        ctx.accounts.vault.reload()?;
+        sync_bond_holding(&ctx.accounts.bond, &ctx.accounts.vault_holding, ctx.accounts.vault.amount)?;
+    }
+
+    // The burned grant no longer counts against the cap
+    let config = &mut ctx.accounts.config;
+    config.total_sold = config.total_sold.checked_sub(unvested).ok_or(VestingError::MathOverflow)?;
+
+    let vesting = &mut ctx.accounts.vesting;
+    vesting.total_amount = vested;
+    vesting.revoked = true;
+
+    msg!("Vesting revoked, {0} unvested tokens burned", unvested);
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.issuer.key(),
+        AuditAction::VestingRevoke,
+        ctx.accounts.vesting.key(),
+        reason_hash
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RevokeVesting<'info> {
+    pub issuer: Signer<'info>,
+
+    #[account(
+        has_one = config,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    #[account(mut)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        has_one = issuer,
+        has_one = mint,
+        seeds = [
+            b"vesting",
+            mint.key().as_ref(),
+            vesting.beneficiary.as_ref(),
+            vesting.seed.to_le_bytes().as_ref(),
+        ],
+        bump = vesting.bump
+    )]
+    pub vesting: Box<Account<'info, Vesting>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: May be uninitialized, read in `sync_bond_holding`
+    #[account(seeds = [b"bond", mint.key().as_ref()], bump)]
+    pub bond: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, updated in `sync_bond_holding`
+    #[account(mut, seeds = [b"bond_holding", vault.key().as_ref()], bump)]
+    pub vault_holding: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"audit_log", mint.key().as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub token_program: Program<'info, Token2022>,
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use crate::state::{ audit_log::*, config::Config, registry::Registry };
+
+pub fn set_compliance_key(
+    ctx: Context<SetComplianceKey>,
+    compliance_key: Pubkey,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    ctx.accounts.config.compliance_key = compliance_key;
+    msg!("Compliance key set to {0}", compliance_key);
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.owner.key(),
+        AuditAction::ComplianceKeyChange,
+        compliance_key,
+        reason_hash
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetComplianceKey<'info> {
+    #[account(
+        has_one = config,
+        seeds = [b"registry", config.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    #[account(mut, has_one = owner)]
+    pub config: Box<Account<'info, Config>>,
+    pub owner: Signer<'info>,
+
+    #[account(mut, seeds = [b"audit_log", config.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
@@ -0,0 +1,203 @@
+use anchor_lang::prelude::*;
+use crate::state::{ audit_log::*, documents::*, registry::Registry };
+use crate::errors::{ DocumentError, RegistryError };
+
+#[event]
+pub struct DocumentUpdated {
+    pub mint: Pubkey,
+    pub name: String,
+    pub uri: String,
+    pub content_hash: [u8; 32],
+    pub slot: u64,
+}
+
+#[event]
+pub struct DocumentRemoved {
+    pub mint: Pubkey,
+    pub name: String,
+    pub slot: u64,
+}
+
+// Creates the document registry of a mint whose hook was initialized before
+// documents existed
+pub fn initialize_documents(ctx: Context<InitializeDocuments>) -> Result<()> {
+    ctx.accounts.documents.set_inner(DocumentRegistry {
+        mint: ctx.accounts.registry.mint,
+        bump: ctx.bumps.documents,
+        versions: 0,
+    });
+    Ok(())
+}
+
+// Adds a document or a new version of it, the previous version stays in the
+// history with the slot it was superseded at
+pub fn set_document(
+    ctx: Context<SetDocument>,
+    name: String,
+    uri: String,
+    content_hash: [u8; 32],
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    require!(!name.is_empty(), DocumentError::EmptyName);
+    require!(name.len() <= MAX_DOCUMENT_NAME_LEN, DocumentError::NameTooLong);
+    require!(uri.len() <= MAX_DOCUMENT_URI_LEN, DocumentError::UriTooLong);
+
+    let clock = Clock::get()?;
+    let mint = ctx.accounts.registry.mint;
+    if ctx.accounts.head.current.is_some() {
+        let previous = ctx.accounts.previous.as_mut().ok_or(DocumentError::NotCurrentVersion)?;
+        previous.until_slot = Some(clock.slot);
+    }
+
+    let documents = &mut ctx.accounts.documents;
+    let index = documents.versions;
+    documents.versions = index.checked_add(1).ok_or(DocumentError::TooManyVersions)?;
+    ctx.accounts.head.set_inner(DocumentHead {
+        mint,
+        current: Some(index),
+        bump: ctx.bumps.head,
+    });
+    ctx.accounts.document.set_inner(Document {
+        mint,
+        index,
+        name: name.clone(),
+        uri: uri.clone(),
+        content_hash,
+        timestamp: clock.unix_timestamp,
+        from_slot: clock.slot,
+        until_slot: None,
+        bump: ctx.bumps.document,
+    });
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::DocumentSet,
+        ctx.accounts.document.key(),
+        reason_hash
+    )?;
+
+    emit!(DocumentUpdated { mint, name, uri, content_hash, slot: clock.slot });
+    Ok(())
+}
+
+pub fn remove_document(ctx: Context<RemoveDocument>, name: String, reason_hash: [u8; 32]) -> Result<()> {
+    let slot = Clock::get()?.slot;
+    ctx.accounts.current.until_slot = Some(slot);
+    ctx.accounts.head.current = None;
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::DocumentRemove,
+        ctx.accounts.current.key(),
+        reason_hash
+    )?;
+
+    emit!(DocumentRemoved { mint: ctx.accounts.registry.mint, name, slot });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeDocuments<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ RegistryError::Unauthorized,
+        seeds = [b"registry", registry.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + DocumentRegistry::INIT_SPACE,
+        seeds = [b"documents", registry.mint.as_ref()],
+        bump
+    )]
+    pub documents: Account<'info, DocumentRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String, uri: String)]
+pub struct SetDocument<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ RegistryError::Unauthorized,
+        seeds = [b"registry", registry.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"documents", registry.mint.as_ref()],
+        bump = documents.bump
+    )]
+    pub documents: Account<'info, DocumentRegistry>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + DocumentHead::INIT_SPACE,
+        seeds = [b"document_head", registry.mint.as_ref(), DocumentHead::seed(&name).as_ref()],
+        bump
+    )]
+    pub head: Account<'info, DocumentHead>,
+
+    // The version in force, required when the name has one
+    #[account(
+        mut,
+        constraint = previous.mint == registry.mint && head.current == Some(previous.index) @
+            DocumentError::NotCurrentVersion
+    )]
+    pub previous: Option<Account<'info, Document>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = Document::space(&name, &uri),
+        seeds = [b"document", registry.mint.as_ref(), documents.versions.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub document: Account<'info, Document>,
+
+    #[account(mut, seeds = [b"audit_log", registry.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct RemoveDocument<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ RegistryError::Unauthorized,
+        seeds = [b"registry", registry.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"document_head", registry.mint.as_ref(), DocumentHead::seed(&name).as_ref()],
+        bump = head.bump
+    )]
+    pub head: Account<'info, DocumentHead>,
+
+    #[account(
+        mut,
+        constraint = current.mint == registry.mint && head.current == Some(current.index) @
+            DocumentError::UnknownDocument
+    )]
+    pub current: Account<'info, Document>,
+
+    #[account(mut, seeds = [b"audit_log", registry.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use crate::state::{ audit_log::*, holding_limits::*, registry::Registry };
+use crate::errors::{ HoldingLimitError, RegistryError };
+
+#[event]
+pub struct HoldingLimitsUpdated {
+    pub mint: Pubkey,
+    pub max_balance: u64,
+    pub max_supply_bps: u16,
+    pub supply_basis: u64,
+    pub exempt: Vec<Pubkey>,
+}
+
+pub fn set_holding_limits(
+    ctx: Context<SetHoldingLimits>,
+    max_balance: u64,
+    max_supply_bps: u16,
+    supply_basis: u64,
+    exempt: Vec<Pubkey>,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    require!(max_supply_bps <= 10_000, HoldingLimitError::InvalidBasisPoints);
+    require!(max_supply_bps == 0 || supply_basis > 0, HoldingLimitError::MissingSupplyBasis);
+    require!(exempt.len() <= MAX_LIMIT_EXEMPTIONS, HoldingLimitError::TooManyExemptions);
+
+    let mint = ctx.accounts.registry.mint;
+    ctx.accounts.holding_limits.set_inner(HoldingLimits {
+        mint,
+        max_balance,
+        max_supply_bps,
+        supply_basis,
+        exempt: exempt.clone(),
+        bump: ctx.bumps.holding_limits,
+    });
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::HoldingLimitsUpdate,
+        ctx.accounts.holding_limits.key(),
+        reason_hash
+    )?;
+
+    emit!(HoldingLimitsUpdated { mint, max_balance, max_supply_bps, supply_basis, exempt });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetHoldingLimits<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ RegistryError::Unauthorized,
+        seeds = [b"registry", registry.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + HoldingLimits::INIT_SPACE,
+        seeds = [b"holding_limits", registry.mint.as_ref()],
+        bump
+    )]
+    pub holding_limits: Account<'info, HoldingLimits>,
+
+    #[account(mut, seeds = [b"audit_log", registry.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub system_program: Program<'info, System>,
+}
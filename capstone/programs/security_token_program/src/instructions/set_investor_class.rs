@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use crate::state::{ audit_log::*, config::Config, investor_class::InvestorClass, registry::Registry };
+
+pub fn set_investor_class(ctx: Context<SetInvestorClass>, class: u8, reason_hash: [u8; 32]) -> Result<()> {
+    ctx.accounts.investor_class.set_inner(InvestorClass {
+        investor: ctx.accounts.investor.key(),
+        class,
+        bump: ctx.bumps.investor_class,
+    });
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.owner.key(),
+        AuditAction::InvestorClassUpdate,
+        ctx.accounts.investor.key(),
+        reason_hash
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetInvestorClass<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        has_one = config,
+        seeds = [b"registry", config.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    #[account(has_one = owner)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: Wallet being assigned to an investor class
+    pub investor: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + InvestorClass::INIT_SPACE,
+        seeds = [b"investor_class", config.key().as_ref(), investor.key().as_ref()],
+        bump
+    )]
+    pub investor_class: Account<'info, InvestorClass>,
+
+    #[account(mut, seeds = [b"audit_log", config.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use crate::state::{ audit_log::*, memo_policy::*, registry::Registry };
+use crate::errors::{ MemoError, RegistryError };
+
+#[event]
+pub struct MemoPolicyUpdated {
+    pub mint: Pubkey,
+    pub required: bool,
+    pub reason_codes: Vec<String>,
+}
+
+pub fn set_memo_policy(
+    ctx: Context<SetMemoPolicy>,
+    required: bool,
+    reason_codes: Vec<String>,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    require!(reason_codes.len() <= MAX_REASON_CODES, MemoError::TooManyReasonCodes);
+    require!(
+        reason_codes
+            .iter()
+            .all(
+                |code|
+                    !code.trim().is_empty() &&
+                    code.trim() == code &&
+                    code.len() <= MAX_REASON_CODE_LEN &&
+                    !code.contains(REASON_CODE_SEPARATOR)
+            ),
+        MemoError::InvalidReasonCode
+    );
+
+    let mint = ctx.accounts.registry.mint;
+    ctx.accounts.memo_policy.set_inner(MemoPolicy {
+        mint,
+        required,
+        reason_codes: reason_codes.clone(),
+        bump: ctx.bumps.memo_policy,
+    });
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::MemoPolicyUpdate,
+        ctx.accounts.memo_policy.key(),
+        reason_hash
+    )?;
+
+    emit!(MemoPolicyUpdated { mint, required, reason_codes });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetMemoPolicy<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ RegistryError::Unauthorized,
+        seeds = [b"registry", registry.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + MemoPolicy::INIT_SPACE,
+        seeds = [b"memo_policy", registry.mint.as_ref()],
+        bump
+    )]
+    pub memo_policy: Account<'info, MemoPolicy>,
+
+    #[account(mut, seeds = [b"audit_log", registry.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub system_program: Program<'info, System>,
+}
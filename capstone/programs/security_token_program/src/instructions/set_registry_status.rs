@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::{ audit_log::*, registry::* };
+use crate::errors::RegistryError;
+
+pub fn set_registry_status(
+    ctx: Context<SetRegistryStatus>,
+    status: SecurityStatus,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    let registry = &mut ctx.accounts.registry;
+    require!(registry.status.can_transition_to(status), RegistryError::InvalidStatusTransition);
+
+    msg!("Security status {:?} -> {:?}", registry.status, status);
+    registry.status = status;
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::StatusChange,
+        registry.mint,
+        reason_hash
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetRegistryStatus<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ RegistryError::Unauthorized,
+        seeds = [b"registry", registry.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(mut, seeds = [b"audit_log", registry.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use spl_type_length_value::state::TlvStateBorrowed;
+use crate::state::{ audit_log::*, registry::Registry, rule_modules::* };
+use crate::errors::{ RegistryError, RuleModuleError };
+use crate::InitializeExtraAccountMetaList;
+
+#[event]
+pub struct RuleModulesUpdated {
+    pub mint: Pubkey,
+    pub modules: Vec<RuleModule>,
+}
+
+// Replaces the ordered list of rule modules. Their program and accounts must
+// already lead the configured hook accounts, see `update_extra_account_meta_list`.
+pub fn set_rule_modules(
+    ctx: Context<SetRuleModules>,
+    modules: Vec<RuleModule>,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    require!(modules.len() <= MAX_RULE_MODULES, RuleModuleError::TooManyModules);
+    check_hook_accounts(&ctx.accounts.extra_account_meta_list.to_account_info(), &modules)?;
+
+    let mint = ctx.accounts.registry.mint;
+    ctx.accounts.rule_modules.set_inner(RuleModules {
+        mint,
+        modules: modules.clone(),
+        bump: ctx.bumps.rule_modules,
+    });
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::RuleModulesUpdate,
+        ctx.accounts.rule_modules.key(),
+        reason_hash
+    )?;
+
+    emit!(RuleModulesUpdated { mint, modules });
+    Ok(())
+}
+
+// Each module program must be the fixed account at its offset in the configured
+// hook accounts, followed by room for its own accounts
+fn check_hook_accounts(meta_list: &AccountInfo, modules: &[RuleModule]) -> Result<()> {
+    let data = meta_list.try_borrow_data()?;
+    let state = TlvStateBorrowed::unpack(&data)?;
+    let metas = ExtraAccountMetaList::unpack_with_tlv_state::<ExecuteInstruction>(&state)?;
+    let built_in = InitializeExtraAccountMetaList::extra_account_metas(&[])?.len();
+    let configured = metas.data().get(built_in..).unwrap_or_default();
+
+    let mut offset = 0;
+    for module in modules {
+        let program = configured.get(offset).ok_or(RuleModuleError::MissingAccounts)?;
+        require!(
+            program.discriminator == 0 && program.address_config == module.program.to_bytes(),
+            RuleModuleError::ProgramMismatch
+        );
+        offset += 1 + (module.account_count as usize);
+    }
+    require!(offset <= configured.len(), RuleModuleError::MissingAccounts);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetRuleModules<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ RegistryError::Unauthorized,
+        has_one = extra_account_meta_list,
+        seeds = [b"registry", registry.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    /// CHECK: ExtraAccountMetaList account of the mint, read in `check_hook_accounts`
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + RuleModules::INIT_SPACE,
+        seeds = [b"rule_modules", registry.mint.as_ref()],
+        bump
+    )]
+    pub rule_modules: Account<'info, RuleModules>,
+
+    #[account(mut, seeds = [b"audit_log", registry.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token_interface::Mint;
+use crate::state::{ approval::SignedApproval, config::Config };
+use crate::ed25519::verify_compliance_approval;
+
+// Meant to be placed right after the ed25519 instruction and before the
+// transfer, so the hook finds a fresh approval for the destination wallet.
+pub fn submit_approval(ctx: Context<SubmitApproval>) -> Result<()> {
+    let wallet = ctx.accounts.wallet.key();
+    let mint = ctx.accounts.mint.key();
+    let expires_at = verify_compliance_approval(
+        &ctx.accounts.instructions_sysvar,
+        &ctx.accounts.config.compliance_key,
+        &wallet,
+        &mint
+    )?;
+
+    ctx.accounts.approval.set_inner(SignedApproval {
+        wallet,
+        mint,
+        expires_at,
+        signer: ctx.accounts.config.compliance_key,
+        bump: ctx.bumps.approval,
+    });
+    msg!("Approval cached for {0} until {1}", wallet, expires_at);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SubmitApproval<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Wallet named in the signed approval
+    pub wallet: UncheckedAccount<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [b"config", mint.key().as_ref()], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + SignedApproval::INIT_SPACE,
+        seeds = [b"approval", mint.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub approval: Account<'info, SignedApproval>,
+
+    /// CHECK: Instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{ self, Mint, TokenAccount, TokenInterface, TransferChecked },
+};
+use crate::state::{
+    config::*,
+    investor_class::InvestorClass,
+    registry::{ Registry, SecurityStatus },
+    subscription::Subscription,
+};
+use crate::instructions::mint_tokens::fill_tranches;
+use crate::compliance::exceeds_holding_limits;
+use crate::errors::{ HoldingLimitError, RegistryError, SaleError };
+
+pub fn subscribe(ctx: Context<Subscribe>, mint_amount: u64) -> Result<()> {
+    require!(ctx.accounts.registry.status == SecurityStatus::Active, RegistryError::NotActive);
+
+    let config = &mut ctx.accounts.config;
+    require!(config.escrow_payments, SaleError::NotEscrowedSale);
+    require!(config.status == SaleStatus::Open, SaleError::SaleNotOpen);
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(now < config.close_ts, SaleError::SaleNotOpen);
+
+    let investor_class = ctx.accounts.investor_class.as_ref().map(|c| c.class);
+    let (cost, fills) = fill_tranches(config, mint_amount, investor_class, now)?;
+    config.total_raised = config.total_raised.checked_add(cost).ok_or(SaleError::MathOverflow)?;
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.payment_source.to_account_info(),
+        mint: ctx.accounts.payment_mint.to_account_info(),
+        to: ctx.accounts.payment_vault.to_account_info(),
+        authority: ctx.accounts.investor.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.payment_token_program.to_account_info(), cpi_accounts);
+    token_interface::transfer_checked(cpi_ctx, cost, ctx.accounts.payment_mint.decimals)?;
+
+    let subscription = &mut ctx.accounts.subscription;
+    if subscription.tokens == 0 {
+        subscription.config = ctx.accounts.config.key();
+        subscription.investor = ctx.accounts.investor.key();
+        subscription.recipient = ctx.accounts.recipient_token_account.key();
+        subscription.bump = ctx.bumps.subscription;
+    }
+    require_keys_eq!(
+        subscription.recipient,
+        ctx.accounts.recipient_token_account.key(),
+        SaleError::InvalidSubscription
+    );
+    subscription.tokens = subscription.tokens.checked_add(mint_amount).ok_or(SaleError::MathOverflow)?;
+    subscription.paid = subscription.paid.checked_add(cost).ok_or(SaleError::MathOverflow)?;
+
+    // Checked again at settlement, the recipient balance may grow meanwhile
+    let recipient = &ctx.accounts.recipient_token_account;
+    let balance = recipient.amount.checked_add(subscription.tokens).ok_or(SaleError::MathOverflow)?;
+    let exceeds_limits = exceeds_holding_limits(
+        &ctx.accounts.holding_limits.to_account_info(),
+        &recipient.key(),
+        &recipient.owner,
+        balance
+    )?;
+    require!(!exceeds_limits, HoldingLimitError::LimitExceeded);
+
+    for fill in fills.iter() {
+        msg!("Tranche {0}: {1} tokens for {2}", fill.tranche, fill.amount, fill.cost);
+    }
+    msg!("Subscribed {0} tokens, {1} held in escrow", mint_amount, cost);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Subscribe<'info> {
+    #[account(mut)]
+    pub investor: Signer<'info>,
+
+    #[account(mut, token::mint = payment_mint)]
+    pub payment_source: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = config,
+        associated_token::token_program = payment_token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // Receives the tokens once the offering is finalized
+    #[account(token::mint = token_mint)]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        has_one = config,
+        seeds = [b"registry", token_mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    #[account(mut, has_one = payment_mint)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        init_if_needed,
+        payer = investor,
+        space = 8 + Subscription::INIT_SPACE,
+        seeds = [b"subscription", config.key().as_ref(), investor.key().as_ref()],
+        bump
+    )]
+    pub subscription: Box<Account<'info, Subscription>>,
+
+    #[account(
+        seeds = [b"investor_class", config.key().as_ref(), investor.key().as_ref()],
+        bump = investor_class.bump
+    )]
+    pub investor_class: Option<Box<Account<'info, InvestorClass>>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: May be uninitialized, read in `exceeds_holding_limits`
+    #[account(seeds = [b"holding_limits", token_mint.key().as_ref()], bump)]
+    pub holding_limits: UncheckedAccount<'info>,
+
+    pub payment_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
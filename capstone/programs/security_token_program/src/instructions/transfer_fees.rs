@@ -0,0 +1,194 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022_extensions::transfer_fee::{
+        harvest_withheld_tokens_to_mint,
+        transfer_fee_set,
+        withdraw_withheld_tokens_from_mint,
+        HarvestWithheldTokensToMint,
+        TransferFeeSetTransferFee,
+        WithdrawWithheldTokensFromMint,
+    },
+    token_interface::{ self, Approve, Mint, Token2022, TokenAccount },
+};
+use crate::state::{ audit_log::*, fee_config::FeeConfig };
+use crate::errors::FeeError;
+
+pub fn update_transfer_fee(
+    ctx: Context<UpdateTransferFee>,
+    basis_points: u16,
+    maximum_fee: u64,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    require!(basis_points <= 10_000, FeeError::InvalidBasisPoints);
+
+    let mint_key = ctx.accounts.mint.key();
+    let signer_seeds: [&[&[u8]]; 1] = [&[b"fee_config", mint_key.as_ref(), &[ctx.accounts.fee_config.bump]]];
+
+    // Token-2022 applies the new fee two epochs from now
+    transfer_fee_set(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferFeeSetTransferFee {
+                token_program_id: ctx.accounts.token_program.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                authority: ctx.accounts.fee_config.to_account_info(),
+            },
+            &signer_seeds
+        ),
+        basis_points,
+        maximum_fee
+    )?;
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::TransferFeeUpdate,
+        mint_key,
+        reason_hash
+    )
+}
+
+// Permissionless: sweeps fees withheld in the token accounts passed as remaining
+// accounts into the mint, then everything withheld on the mint into the fee vault.
+pub fn harvest_withheld_fees<'info>(
+    ctx: Context<'_, '_, 'info, 'info, HarvestWithheldFees<'info>>
+) -> Result<()> {
+    if !ctx.remaining_accounts.is_empty() {
+        harvest_withheld_tokens_to_mint(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), HarvestWithheldTokensToMint {
+                token_program_id: ctx.accounts.token_program.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+            }),
+            ctx.remaining_accounts.to_vec()
+        )?;
+    }
+
+    let mint_key = ctx.accounts.mint.key();
+    let signer_seeds: [&[&[u8]]; 1] = [&[b"fee_config", mint_key.as_ref(), &[ctx.accounts.fee_config.bump]]];
+
+    withdraw_withheld_tokens_from_mint(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            WithdrawWithheldTokensFromMint {
+                token_program_id: ctx.accounts.token_program.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                destination: ctx.accounts.fee_vault.to_account_info(),
+                authority: ctx.accounts.fee_config.to_account_info(),
+            },
+            &signer_seeds
+        )
+    )?;
+
+    ctx.accounts.fee_vault.reload()?;
+    msg!("Fee vault balance: {0}", ctx.accounts.fee_vault.amount);
+    Ok(())
+}
+
+// Like vesting releases, the vault cannot be moved by a CPI from this program
+// because the hook would re-enter it. The admin is approved as delegate for
+// `amount` and withdraws with a regular `transfer_checked` through the hook.
+pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64, reason_hash: [u8; 32]) -> Result<()> {
+    let vault = &ctx.accounts.fee_vault;
+    let allowance = vault.delegated_amount.checked_add(amount).ok_or(FeeError::MathOverflow)?;
+    require!(allowance <= vault.amount, FeeError::InsufficientFees);
+
+    let mint_key = ctx.accounts.mint.key();
+    let signer_seeds: [&[&[u8]]; 1] = [&[b"fee_config", mint_key.as_ref(), &[ctx.accounts.fee_config.bump]]];
+
+    token_interface::approve(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Approve {
+                to: ctx.accounts.fee_vault.to_account_info(),
+                delegate: ctx.accounts.admin.to_account_info(),
+                authority: ctx.accounts.fee_config.to_account_info(),
+            },
+            &signer_seeds
+        ),
+        allowance
+    )?;
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::FeeWithdraw,
+        ctx.accounts.fee_vault.key(),
+        reason_hash
+    )?;
+
+    msg!("Approved {0} in fees for withdrawal", amount);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateTransferFee<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        has_one = admin,
+        has_one = mint,
+        seeds = [b"fee_config", mint.key().as_ref()],
+        bump = fee_config.bump
+    )]
+    pub fee_config: Account<'info, FeeConfig>,
+
+    #[account(mut, seeds = [b"audit_log", mint.key().as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct HarvestWithheldFees<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(has_one = mint, seeds = [b"fee_config", mint.key().as_ref()], bump = fee_config.bump)]
+    pub fee_config: Account<'info, FeeConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = fee_config,
+        associated_token::token_program = token_program
+    )]
+    pub fee_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    pub admin: Signer<'info>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        has_one = admin,
+        has_one = mint,
+        seeds = [b"fee_config", mint.key().as_ref()],
+        bump = fee_config.bump
+    )]
+    pub fee_config: Account<'info, FeeConfig>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = fee_config,
+        associated_token::token_program = token_program
+    )]
+    pub fee_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, seeds = [b"audit_log", mint.key().as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub token_program: Program<'info, Token2022>,
+}
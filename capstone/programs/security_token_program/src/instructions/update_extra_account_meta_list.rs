@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{ self, Transfer };
+use anchor_spl::token_interface::Mint;
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use crate::state::{ audit_log::*, extra_metas::HookAccountConfig };
+use crate::{ check_hook_authority, InitializeExtraAccountMetaList };
+
+// Replaces the issuer-configured accounts of the hook, resizing the list account.
+// The authority pays for growth and gets back the rent freed by shrinking.
+// The built-in accounts are rewritten as well, so a list written before the
+// `TransferHook` accounts changed is migrated by calling this with its configured
+// accounts unchanged. Until then transfers of the mint fail the account checks.
+pub fn update_extra_account_meta_list(
+    ctx: Context<UpdateExtraAccountMetaList>,
+    extra_account_metas: Vec<HookAccountConfig>,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    check_hook_authority(&ctx.accounts.mint.to_account_info(), &ctx.accounts.authority.key())?;
+
+    let extra_account_metas = InitializeExtraAccountMetaList::extra_account_metas(
+        &extra_account_metas
+    )?;
+    let new_size = ExtraAccountMetaList::size_of(extra_account_metas.len())?;
+    let meta_list = ctx.accounts.extra_account_meta_list.to_account_info();
+
+    if new_size > meta_list.data_len() {
+        let top_up = Rent::get()?.minimum_balance(new_size).saturating_sub(meta_list.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(ctx.accounts.system_program.to_account_info(), Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: meta_list.clone(),
+                }),
+                top_up
+            )?;
+        }
+        meta_list.realloc(new_size, false)?;
+    }
+
+    ExtraAccountMetaList::update::<ExecuteInstruction>(
+        &mut meta_list.try_borrow_mut_data()?,
+        &extra_account_metas
+    )?;
+
+    if new_size < meta_list.data_len() {
+        meta_list.realloc(new_size, false)?;
+        let excess = meta_list.lamports().saturating_sub(Rent::get()?.minimum_balance(new_size));
+        meta_list.sub_lamports(excess)?;
+        ctx.accounts.authority.add_lamports(excess)?;
+    }
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.authority.key(),
+        AuditAction::HookAccountsUpdate,
+        ctx.accounts.extra_account_meta_list.key(),
+        reason_hash
+    )?;
+
+    msg!("Extra account metas updated, {0} accounts", extra_account_metas.len());
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: ExtraAccountMetaList account, rewritten in place
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, seeds = [b"audit_log", mint.key().as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub system_program: Program<'info, System>,
+}
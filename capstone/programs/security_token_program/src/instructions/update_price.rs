@@ -1,14 +1,39 @@
 use anchor_lang::prelude::*;
-use crate::state::config::Config;
+use crate::state::{ audit_log::*, config::Config, registry::Registry };
+use crate::errors::{ RegistryError, SaleError };
 
-pub fn update_price(ctx: Context<UpdatePrice>, new_price: u64) -> Result<()> {
-    ctx.accounts.config.price = new_price;
+pub fn update_price(
+    ctx: Context<UpdatePrice>,
+    tranche_index: u8,
+    new_price: u64,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    require!(ctx.accounts.registry.status.allows_changes(), RegistryError::NotActive);
+
+    let tranche = ctx.accounts.config.tranches
+        .get_mut(tranche_index as usize)
+        .ok_or(SaleError::InvalidTranche)?;
+    tranche.price = new_price;
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.owner.key(),
+        AuditAction::PriceUpdate,
+        ctx.accounts.config.key(),
+        reason_hash
+    )?;
     Ok(())
 }
 
 #[derive(Accounts)]
 pub struct UpdatePrice<'info> {
+    #[account(
+        has_one = config,
+        seeds = [b"registry", config.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
     #[account(mut, has_one = owner)]
     pub config: Box<Account<'info, Config>>,
     pub owner: Signer<'info>,
+    #[account(mut, seeds = [b"audit_log", config.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
 }
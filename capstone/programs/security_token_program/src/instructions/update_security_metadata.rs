@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{ self, Transfer };
+use anchor_spl::token_interface::{
+    spl_token_2022::{ extension::{ BaseStateWithExtensions, StateWithExtensions }, state::Mint as MintState },
+    spl_token_metadata_interface::state::{ Field, TokenMetadata },
+    token_metadata_update_field,
+    Mint,
+    Token2022,
+    TokenMetadataUpdateField,
+};
+use crate::state::audit_log::*;
+
+// `field` is "name", "symbol", "uri" or any additional key such as "isin",
+// "cusip" or "document_hash". Token-2022 checks the issuer is the update authority.
+pub fn update_security_metadata(
+    ctx: Context<UpdateSecurityMetadata>,
+    field: String,
+    value: String,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    let field = match field.as_str() {
+        "name" => Field::Name,
+        "symbol" => Field::Symbol,
+        "uri" => Field::Uri,
+        _ => Field::Key(field),
+    };
+
+    // Fund the mint for the resized metadata before Token-2022 reallocates it
+    let mint_info = ctx.accounts.mint.to_account_info();
+    let (current_len, additional_len) = {
+        let mint_data = mint_info.data.borrow();
+        let mint = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+        let mut metadata = mint.get_variable_len_extension::<TokenMetadata>()?;
+        let old_size = metadata.tlv_size_of()?;
+        metadata.update(field.clone(), value.clone());
+        (mint_data.len(), metadata.tlv_size_of()?.saturating_sub(old_size))
+    };
+    let required = Rent::get()?.minimum_balance(current_len + additional_len);
+    let top_up = required.saturating_sub(mint_info.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(ctx.accounts.system_program.to_account_info(), Transfer {
+                from: ctx.accounts.issuer.to_account_info(),
+                to: mint_info.clone(),
+            }),
+            top_up
+        )?;
+    }
+
+    token_metadata_update_field(
+        CpiContext::new(ctx.accounts.token_program.to_account_info(), TokenMetadataUpdateField {
+            token_program_id: ctx.accounts.token_program.to_account_info(),
+            metadata: mint_info,
+            update_authority: ctx.accounts.issuer.to_account_info(),
+        }),
+        field,
+        value
+    )?;
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.issuer.key(),
+        AuditAction::MetadataUpdate,
+        ctx.accounts.mint.key(),
+        reason_hash
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateSecurityMetadata<'info> {
+    #[account(mut)]
+    pub issuer: Signer<'info>,
+
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, seeds = [b"audit_log", mint.key().as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub token_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
@@ -1,12 +1,24 @@
 use anchor_lang::prelude::*;
-use crate::state::whitelist::*;
+use crate::state::{ audit_log::*, registry::Registry, whitelist::* };
+use crate::errors::WhiteListError;
 
-pub fn whitelist_add(ctx: Context<AddToWhiteList>) -> Result<()> {
-    if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
-        panic!("Only the authority can add to the white list!");
+// Adding an account that is already listed is a no-op, as in `whitelist_batch`
+pub fn whitelist_add(ctx: Context<AddToWhiteList>, reason_hash: [u8; 32]) -> Result<()> {
+    require_keys_eq!(ctx.accounts.white_list.authority, ctx.accounts.signer.key(), WhiteListError::Unauthorized);
+
+    let new_account = ctx.accounts.new_account.key();
+    if ctx.accounts.white_list.white_list.contains(&new_account) {
+        msg!("Account already white listed! {0}", new_account.to_string());
+        return Ok(());
     }
 
-    ctx.accounts.white_list.white_list.push(ctx.accounts.new_account.key());
+    ctx.accounts.white_list.white_list.push(new_account);
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.signer.key(),
+        AuditAction::WhitelistAdd,
+        ctx.accounts.new_account.key(),
+        reason_hash
+    )?;
     msg!("New account white listed! {0}", ctx.accounts.new_account.key().to_string());
     msg!("White list length! {0}", ctx.accounts.white_list.white_list.len());
 
@@ -18,12 +30,24 @@ pub struct AddToWhiteList<'info> {
     /// CHECK: New account to add to white list
     #[account()]
     pub new_account: AccountInfo<'info>,
+    #[account(
+        has_one = white_list,
+        seeds = [b"registry", registry.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
     #[account(
         mut,
-        seeds = [b"white_list"],
-        bump
+        realloc = WhiteList::space(
+            white_list.white_list.len() + usize::from(!white_list.white_list.contains(new_account.key))
+        ),
+        realloc::payer = signer,
+        realloc::zero = false
     )]
     pub white_list: Account<'info, WhiteList>,
+    #[account(mut, seeds = [b"audit_log", registry.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
     #[account(mut)]
     pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
\ No newline at end of file
@@ -0,0 +1,190 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount };
+use crate::state::{ application::*, audit_log::*, registry::Registry, whitelist::* };
+use crate::errors::WhiteListError;
+
+#[event]
+pub struct WhitelistRequested {
+    pub mint: Pubkey,
+    pub applicant: Pubkey,
+    pub token_account: Pubkey,
+    pub document_hash: [u8; 32],
+}
+
+#[event]
+pub struct ApplicationResolved {
+    pub mint: Pubkey,
+    pub applicant: Pubkey,
+    pub token_account: Pubkey,
+    pub approved: bool,
+}
+
+pub fn request_whitelist(ctx: Context<RequestWhitelist>, document_hash: [u8; 32]) -> Result<()> {
+    let token_account = ctx.accounts.token_account.key();
+    require!(
+        !ctx.accounts.white_list.white_list.contains(&token_account),
+        WhiteListError::AlreadyWhitelisted
+    );
+
+    let mint = ctx.accounts.mint.key();
+    let applicant = ctx.accounts.applicant.key();
+    ctx.accounts.application.set_inner(WhitelistApplication {
+        mint,
+        applicant,
+        token_account,
+        document_hash,
+        created_at: Clock::get()?.unix_timestamp,
+        bump: ctx.bumps.application,
+    });
+
+    emit!(WhitelistRequested { mint, applicant, token_account, document_hash });
+    Ok(())
+}
+
+pub fn approve_application(ctx: Context<ApproveApplication>, reason_hash: [u8; 32]) -> Result<()> {
+    let application = &ctx.accounts.application;
+    let white_list = &mut ctx.accounts.white_list;
+    if !white_list.white_list.contains(&application.token_account) {
+        white_list.white_list.push(application.token_account);
+    }
+
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.authority.key(),
+        AuditAction::WhitelistAdd,
+        application.token_account,
+        reason_hash
+    )?;
+    emit!(ApplicationResolved {
+        mint: application.mint,
+        applicant: application.applicant,
+        token_account: application.token_account,
+        approved: true,
+    });
+    Ok(())
+}
+
+pub fn reject_application(ctx: Context<RejectApplication>, reason_hash: [u8; 32]) -> Result<()> {
+    let application = &ctx.accounts.application;
+    ctx.accounts.audit_log.load_mut()?.append(
+        ctx.accounts.authority.key(),
+        AuditAction::ApplicationRejected,
+        application.token_account,
+        reason_hash
+    )?;
+    emit!(ApplicationResolved {
+        mint: application.mint,
+        applicant: application.applicant,
+        token_account: application.token_account,
+        approved: false,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RequestWhitelist<'info> {
+    #[account(mut)]
+    pub applicant: Signer<'info>,
+
+    #[account(
+        has_one = mint,
+        has_one = white_list,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub white_list: Account<'info, WhiteList>,
+
+    // The account to whitelist, owned by the applicant
+    #[account(token::mint = mint, token::authority = applicant)]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = applicant,
+        space = 8 + WhitelistApplication::INIT_SPACE,
+        seeds = [b"application", mint.key().as_ref(), applicant.key().as_ref()],
+        bump
+    )]
+    pub application: Account<'info, WhitelistApplication>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveApplication<'info> {
+    // White list authority, acting as compliance officer
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = white_list,
+        seeds = [b"registry", application.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        has_one = authority @ WhiteListError::Unauthorized,
+        // Grows only if the account is not already listed
+        realloc = WhiteList::space(
+            white_list.white_list.len() + usize::from(!white_list.white_list.contains(&application.token_account))
+        ),
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub white_list: Account<'info, WhiteList>,
+
+    #[account(
+        mut,
+        close = applicant,
+        has_one = applicant,
+        seeds = [b"application", application.mint.as_ref(), applicant.key().as_ref()],
+        bump = application.bump
+    )]
+    pub application: Account<'info, WhitelistApplication>,
+
+    /// CHECK: Receives the rent of the application
+    #[account(mut)]
+    pub applicant: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"audit_log", application.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RejectApplication<'info> {
+    // White list authority, acting as compliance officer
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = white_list,
+        seeds = [b"registry", application.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(has_one = authority @ WhiteListError::Unauthorized)]
+    pub white_list: Account<'info, WhiteList>,
+
+    #[account(
+        mut,
+        close = applicant,
+        has_one = applicant,
+        seeds = [b"application", application.mint.as_ref(), applicant.key().as_ref()],
+        bump = application.bump
+    )]
+    pub application: Account<'info, WhitelistApplication>,
+
+    /// CHECK: Receives the rent of the application
+    #[account(mut)]
+    pub applicant: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"audit_log", application.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
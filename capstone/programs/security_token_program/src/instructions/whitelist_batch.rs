@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use crate::state::{ audit_log::*, registry::Registry, whitelist::* };
+use crate::errors::WhiteListError;
+
+// Adds and removes many entries in one go. The account is resized up front to
+// the final length, with the authority paying (or receiving) the rent
+// difference; adding an entry that is already listed is a no-op.
+pub fn whitelist_batch(
+    ctx: Context<WhiteListBatch>,
+    add: Vec<Pubkey>,
+    remove: Vec<Pubkey>,
+    reason_hash: [u8; 32]
+) -> Result<()> {
+    let white_list = &mut ctx.accounts.white_list.white_list;
+    let mut audit_log = ctx.accounts.audit_log.load_mut()?;
+    let authority = ctx.accounts.authority.key();
+
+    for account in remove {
+        if let Some(index) = white_list.iter().position(|listed| *listed == account) {
+            white_list.remove(index);
+            audit_log.append(authority, AuditAction::WhitelistRemove, account, reason_hash)?;
+        }
+    }
+    for account in add {
+        if !white_list.contains(&account) {
+            white_list.push(account);
+            audit_log.append(authority, AuditAction::WhitelistAdd, account, reason_hash)?;
+        }
+    }
+
+    msg!("White list length! {0}", white_list.len());
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(add: Vec<Pubkey>, remove: Vec<Pubkey>)]
+pub struct WhiteListBatch<'info> {
+    #[account(
+        has_one = white_list,
+        seeds = [b"registry", registry.mint.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        has_one = authority @ WhiteListError::Unauthorized,
+        realloc = WhiteList::space(white_list.len_after_batch(&add, &remove)),
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    #[account(mut, seeds = [b"audit_log", registry.mint.as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
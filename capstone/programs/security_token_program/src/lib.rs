@@ -1,26 +1,48 @@
 #![allow(unexpected_cfgs, ambiguous_glob_reexports)]
 use std::cell::RefMut;
 
-use anchor_lang::prelude::*;
+use anchor_lang::{
+    prelude::*,
+    solana_program::sysvar,
+    system_program::{ self, CreateAccount },
+};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_2022::spl_token_2022::{
-        extension::{
-            transfer_hook::TransferHookAccount,
-            BaseStateWithExtensionsMut,
-            PodStateWithExtensionsMut,
+    token_2022::{
+        self as token_2022_cpi,
+        spl_token_2022::{
+            extension::{
+                transfer_hook::TransferHookAccount,
+                BaseStateWithExtensionsMut,
+                ExtensionType,
+                PodStateWithExtensionsMut,
+            },
+            pod::PodAccount,
         },
-        pod::PodAccount,
+        InitializeMint2,
+    },
+    token_2022_extensions::{
+        metadata_pointer::{ metadata_pointer_initialize, MetadataPointerInitialize },
+        token_metadata::{
+            token_metadata_initialize,
+            token_metadata_update_field,
+            TokenMetadataInitialize,
+            TokenMetadataUpdateField,
+        },
+        transfer_fee::{ transfer_fee_initialize, TransferFeeInitialize },
+        transfer_hook::{ transfer_hook_initialize, TransferHookInitialize },
+        default_account_state::{ default_account_state_initialize, DefaultAccountStateInitialize },
     },
     token_interface::{
         spl_pod::optional_keys::OptionalNonZeroPubkey,
+        spl_token_metadata_interface::state::Field,
         spl_token_2022::{
             extension::{
                 transfer_hook::TransferHook as TransferHookExtension,
                 BaseStateWithExtensions,
                 StateWithExtensions,
             },
-            state::Mint as MintState,
+            state::{ AccountState, Mint as MintState },
         },
         Mint,
         Token2022,
@@ -38,31 +60,389 @@ pub mod state;
 pub use state::*;
 pub mod errors;
 pub use errors::*;
+pub mod ed25519;
+pub mod settlement;
+pub mod compliance;
+pub use compliance::*;
 
 #[program]
 pub mod security_token_program {
     use super::*;
-    // create a mint account that specifies this program as the transfer hook program
-    pub fn initialize(ctx: Context<Initialize>, _decimals: u8) -> Result<()> {
+    // create a mint account that specifies this program as the transfer hook program,
+    // optionally charging a transfer fee withheld for the issuer, carrying
+    // on-mint security metadata and starting new token accounts frozen
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        decimals: u8,
+        transfer_fee: Option<TransferFeeParams>,
+        metadata: Option<SecurityMetadata>,
+        default_frozen: bool
+    ) -> Result<()> {
+        ctx.accounts.create_mint(decimals, transfer_fee, metadata.as_ref(), default_frozen, &ctx.bumps)?;
+        if let Some(metadata) = metadata {
+            ctx.accounts.initialize_metadata(metadata)?;
+        }
         ctx.accounts.check_mint_data()?;
         Ok(())
     }
 
-    pub fn update_price(ctx: Context<UpdatePrice>, new_price: u64) -> Result<()> {
-        update_price::update_price(ctx, new_price)
+    pub fn update_security_metadata(
+        ctx: Context<UpdateSecurityMetadata>,
+        field: String,
+        value: String,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        update_security_metadata::update_security_metadata(ctx, field, value, reason_hash)
+    }
+
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        max_supply: u64,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        initialize_config::initialize_config(ctx, max_supply, reason_hash)
+    }
+
+    pub fn add_tranche(
+        ctx: Context<AddTranche>,
+        price: u64,
+        cap: u64,
+        start_ts: i64,
+        end_ts: i64,
+        whitelist_class: Option<u8>,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        add_tranche::add_tranche(ctx, price, cap, start_ts, end_ts, whitelist_class, reason_hash)
+    }
+
+    pub fn update_price(
+        ctx: Context<UpdatePrice>,
+        tranche_index: u8,
+        new_price: u64,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        update_price::update_price(ctx, tranche_index, new_price, reason_hash)
+    }
+
+    pub fn approve_holder(ctx: Context<ApproveHolder>, reason_hash: [u8; 32]) -> Result<()> {
+        approve_holder::approve_holder(ctx, reason_hash)
+    }
+
+    pub fn revoke_holder(ctx: Context<RevokeHolder>, reason_hash: [u8; 32]) -> Result<()> {
+        approve_holder::revoke_holder(ctx, reason_hash)
+    }
+
+    pub fn set_investor_class(
+        ctx: Context<SetInvestorClass>,
+        class: u8,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        set_investor_class::set_investor_class(ctx, class, reason_hash)
+    }
+
+    pub fn mint_tokens(ctx: Context<MintTokens>, mint_amount: u64) -> Result<()> {
+        mint_tokens::mint_tokens(ctx, mint_amount)
+    }
+
+    pub fn configure_escrow(
+        ctx: Context<ConfigureEscrow>,
+        soft_cap: u64,
+        close_ts: i64,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        configure_escrow::configure_escrow(ctx, soft_cap, close_ts, reason_hash)
+    }
+
+    pub fn subscribe(ctx: Context<Subscribe>, mint_amount: u64) -> Result<()> {
+        subscribe::subscribe(ctx, mint_amount)
+    }
+
+    pub fn finalize_offering<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FinalizeOffering<'info>>
+    ) -> Result<()> {
+        finalize_offering::finalize_offering(ctx)
+    }
+
+    pub fn cancel_offering(ctx: Context<CancelOffering>, reason_hash: [u8; 32]) -> Result<()> {
+        cancel_offering::cancel_offering(ctx, reason_hash)
+    }
+
+    pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+        claim_refund::claim_refund(ctx)
+    }
+
+    pub fn create_vesting(
+        ctx: Context<CreateVesting>,
+        seed: u64,
+        amount: u64,
+        schedule: VestingSchedule
+    ) -> Result<()> {
+        create_vesting::create_vesting(ctx, seed, amount, schedule)
+    }
+
+    pub fn release_vested(ctx: Context<ReleaseVested>) -> Result<()> {
+        release_vested::release_vested(ctx)
+    }
+
+    pub fn achieve_milestone(
+        ctx: Context<AchieveMilestone>,
+        index: u8,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        achieve_milestone::achieve_milestone(ctx, index, reason_hash)
+    }
+
+    pub fn revoke_vesting(ctx: Context<RevokeVesting>, reason_hash: [u8; 32]) -> Result<()> {
+        revoke_vesting::revoke_vesting(ctx, reason_hash)
+    }
+
+    pub fn create_bond(
+        ctx: Context<CreateBond>,
+        face_value: u64,
+        coupon_rate_bps: u16,
+        coupon_interval: i64,
+        start_ts: i64,
+        maturity_ts: i64,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        bond_payments::create_bond(
+            ctx,
+            face_value,
+            coupon_rate_bps,
+            coupon_interval,
+            start_ts,
+            maturity_ts,
+            reason_hash
+        )
+    }
+
+    pub fn accrue_coupon(ctx: Context<AccrueCoupon>) -> Result<()> {
+        bond_payments::accrue_coupon(ctx)
+    }
+
+    pub fn register_bond_holding(ctx: Context<RegisterBondHolding>) -> Result<()> {
+        bond_payments::register_bond_holding(ctx)
+    }
+
+    pub fn claim_coupon(ctx: Context<ClaimCoupon>) -> Result<()> {
+        bond_payments::claim_coupon(ctx)
+    }
+
+    pub fn redeem_bond(ctx: Context<RedeemBond>) -> Result<()> {
+        bond_payments::redeem_bond(ctx)
+    }
+
+    pub fn create_fund(ctx: Context<CreateFund>, nav_publisher: Pubkey) -> Result<()> {
+        fund_dealing::create_fund(ctx, nav_publisher)
+    }
+
+    pub fn set_nav_publisher(
+        ctx: Context<SetNavPublisher>,
+        nav_publisher: Pubkey,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        fund_dealing::set_nav_publisher(ctx, nav_publisher, reason_hash)
+    }
+
+    pub fn strike_nav(ctx: Context<StrikeNav>, nav: u64, reason_hash: [u8; 32]) -> Result<()> {
+        fund_dealing::strike_nav(ctx, nav, reason_hash)
+    }
+
+    pub fn request_subscription(ctx: Context<RequestSubscription>, amount: u64) -> Result<()> {
+        fund_dealing::request_subscription(ctx, amount)
+    }
+
+    pub fn cancel_subscription(ctx: Context<CancelSubscription>) -> Result<()> {
+        fund_dealing::cancel_subscription(ctx)
+    }
+
+    pub fn request_redemption(ctx: Context<RequestRedemption>, shares: u64) -> Result<()> {
+        fund_dealing::request_redemption(ctx, shares)
+    }
+
+    pub fn settle_subscription(ctx: Context<SettleSubscription>) -> Result<()> {
+        fund_dealing::settle_subscription(ctx)
+    }
+
+    pub fn settle_redemption(ctx: Context<SettleRedemption>) -> Result<()> {
+        fund_dealing::settle_redemption(ctx)
+    }
+
+    pub fn withdraw_fund_cash(
+        ctx: Context<WithdrawFundCash>,
+        amount: u64,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        fund_dealing::withdraw_fund_cash(ctx, amount, reason_hash)
+    }
+
+    pub fn create_market(
+        ctx: Context<CreateMarket>,
+        lockup_until: i64,
+        allowed_classes: Vec<u8>,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        create_market::create_market(ctx, lockup_until, allowed_classes, reason_hash)
+    }
+
+    pub fn post_order(
+        ctx: Context<PostOrder>,
+        nonce: u64,
+        side: OrderSide,
+        price: u64,
+        amount: u64
+    ) -> Result<()> {
+        post_order::post_order(ctx, nonce, side, price, amount)
+    }
+
+    pub fn fill_order(ctx: Context<FillOrder>, amount: u64) -> Result<()> {
+        fill_order::fill_order(ctx, amount)
+    }
+
+    pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+        cancel_order::cancel_order(ctx)
+    }
+
+    pub fn close_order(ctx: Context<CloseOrder>) -> Result<()> {
+        cancel_order::close_order(ctx)
+    }
+
+    pub fn whitelist_add(ctx: Context<AddToWhiteList>, reason_hash: [u8; 32]) -> Result<()> {
+        whitelist_add::whitelist_add(ctx, reason_hash)
+    }
+
+    pub fn whitelist_batch(
+        ctx: Context<WhiteListBatch>,
+        add: Vec<Pubkey>,
+        remove: Vec<Pubkey>,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        whitelist_batch::whitelist_batch(ctx, add, remove, reason_hash)
+    }
+
+    pub fn request_whitelist(ctx: Context<RequestWhitelist>, document_hash: [u8; 32]) -> Result<()> {
+        whitelist_applications::request_whitelist(ctx, document_hash)
+    }
+
+    pub fn approve_application(ctx: Context<ApproveApplication>, reason_hash: [u8; 32]) -> Result<()> {
+        whitelist_applications::approve_application(ctx, reason_hash)
+    }
+
+    pub fn reject_application(ctx: Context<RejectApplication>, reason_hash: [u8; 32]) -> Result<()> {
+        whitelist_applications::reject_application(ctx, reason_hash)
+    }
+
+    pub fn add_kyc_provider(
+        ctx: Context<UpdateKycProviders>,
+        provider: Pubkey,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        kyc_providers::add_kyc_provider(ctx, provider, reason_hash)
+    }
+
+    pub fn remove_kyc_provider(
+        ctx: Context<UpdateKycProviders>,
+        provider: Pubkey,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        kyc_providers::remove_kyc_provider(ctx, provider, reason_hash)
+    }
+
+    pub fn issue_attestation(
+        ctx: Context<IssueAttestation>,
+        level: u8,
+        expires_at: i64,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        issue_attestation::issue_attestation(ctx, level, expires_at, reason_hash)
+    }
+
+    pub fn revoke_attestation(ctx: Context<RevokeAttestation>, reason_hash: [u8; 32]) -> Result<()> {
+        issue_attestation::revoke_attestation(ctx, reason_hash)
+    }
+
+    pub fn set_holding_limits(
+        ctx: Context<SetHoldingLimits>,
+        max_balance: u64,
+        max_supply_bps: u16,
+        supply_basis: u64,
+        exempt: Vec<Pubkey>,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        set_holding_limits::set_holding_limits(ctx, max_balance, max_supply_bps, supply_basis, exempt, reason_hash)
+    }
+
+    pub fn set_memo_policy(
+        ctx: Context<SetMemoPolicy>,
+        required: bool,
+        reason_codes: Vec<String>,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        set_memo_policy::set_memo_policy(ctx, required, reason_codes, reason_hash)
+    }
+
+    pub fn set_compliance_key(
+        ctx: Context<SetComplianceKey>,
+        compliance_key: Pubkey,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        set_compliance_key::set_compliance_key(ctx, compliance_key, reason_hash)
     }
 
-    pub fn whitelist_add(ctx: Context<AddToWhiteList>) -> Result<()> {
-        whitelist_add::whitelist_add(ctx)
+    pub fn submit_approval(ctx: Context<SubmitApproval>) -> Result<()> {
+        submit_approval::submit_approval(ctx)
     }
 
-    #[interface(spl_transfer_hook_interface::initialize_extra_account_meta_list)]
+    pub fn update_transfer_fee(
+        ctx: Context<UpdateTransferFee>,
+        basis_points: u16,
+        maximum_fee: u64,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        transfer_fees::update_transfer_fee(ctx, basis_points, maximum_fee, reason_hash)
+    }
+
+    pub fn harvest_withheld_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, HarvestWithheldFees<'info>>
+    ) -> Result<()> {
+        transfer_fees::harvest_withheld_fees(ctx)
+    }
+
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64, reason_hash: [u8; 32]) -> Result<()> {
+        transfer_fees::withdraw_fees(ctx, amount, reason_hash)
+    }
+
+    // Takes issuer config rather than the interface's `Vec<ExtraAccountMeta>`, so
+    // it keeps Anchor's own discriminator instead of the interface one
     pub fn initialize_extra_account_meta_list(
-        ctx: Context<InitializeExtraAccountMetaList>
+        ctx: Context<InitializeExtraAccountMetaList>,
+        extra_account_metas: Vec<HookAccountConfig>
     ) -> Result<()> {
+        check_hook_authority(&ctx.accounts.mint.to_account_info(), &ctx.accounts.payer.key())?;
+
         ctx.accounts.white_list.authority = ctx.accounts.payer.key();
+        ctx.accounts.kyc_providers.authority = ctx.accounts.payer.key();
+        ctx.accounts.registry.set_inner(Registry {
+            mint: ctx.accounts.mint.key(),
+            admin: ctx.accounts.payer.key(),
+            status: SecurityStatus::Draft,
+            extra_account_meta_list: ctx.accounts.extra_account_meta_list.key(),
+            white_list: ctx.accounts.white_list.key(),
+            kyc_providers: ctx.accounts.kyc_providers.key(),
+            config: Pubkey::default(),
+            fee_config: ctx.accounts.fee_config.as_ref().map(|f| f.key()).unwrap_or_default(),
+            bump: ctx.bumps.registry,
+        });
+        ctx.accounts.audit_log.load_init()?.mint = ctx.accounts.mint.key();
+        ctx.accounts.documents.set_inner(DocumentRegistry {
+            mint: ctx.accounts.mint.key(),
+            bump: ctx.bumps.documents,
+            versions: 0,
+        });
 
-        let extra_account_metas = InitializeExtraAccountMetaList::extra_account_metas()?;
+        let extra_account_metas = InitializeExtraAccountMetaList::extra_account_metas(
+            &extra_account_metas
+        )?;
 
         ExtraAccountMetaList::init::<ExecuteInstruction>(
             &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
@@ -71,17 +451,101 @@ pub mod security_token_program {
         Ok(())
     }
 
+    pub fn update_extra_account_meta_list(
+        ctx: Context<UpdateExtraAccountMetaList>,
+        extra_account_metas: Vec<HookAccountConfig>,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        update_extra_account_meta_list::update_extra_account_meta_list(ctx, extra_account_metas, reason_hash)
+    }
+
+    pub fn initialize_documents(ctx: Context<InitializeDocuments>) -> Result<()> {
+        set_document::initialize_documents(ctx)
+    }
+
+    pub fn set_document(
+        ctx: Context<SetDocument>,
+        name: String,
+        uri: String,
+        content_hash: [u8; 32],
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        set_document::set_document(ctx, name, uri, content_hash, reason_hash)
+    }
+
+    pub fn remove_document(
+        ctx: Context<RemoveDocument>,
+        name: String,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        set_document::remove_document(ctx, name, reason_hash)
+    }
+
+    pub fn set_registry_status(
+        ctx: Context<SetRegistryStatus>,
+        status: SecurityStatus,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        set_registry_status::set_registry_status(ctx, status, reason_hash)
+    }
+
+    pub fn set_rule_modules(
+        ctx: Context<SetRuleModules>,
+        modules: Vec<RuleModule>,
+        reason_hash: [u8; 32]
+    ) -> Result<()> {
+        set_rule_modules::set_rule_modules(ctx, modules, reason_hash)
+    }
+
     #[interface(spl_transfer_hook_interface::execute)]
-    pub fn transfer_hook(ctx: Context<TransferHook>, _amount: u64) -> Result<()> {
+    pub fn transfer_hook<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TransferHook<'info>>,
+        amount: u64
+    ) -> Result<()> {
         // Fail this instruction if it is not called from within a transfer hook
         check_is_transferring(&ctx)?;
 
-        if !ctx.accounts.white_list.white_list.contains(&ctx.accounts.destination_token.key()) {
-            panic!("The receiving account is not whitelisted");
+        // Token-2022 runs the hook after moving the tokens
+        let destination_balance = ctx.accounts.destination_token.amount;
+        let restriction = ctx.accounts.detect_restriction(
+            amount,
+            destination_balance,
+            ctx.remaining_accounts
+        )?;
+        if restriction != RestrictionCode::Success {
+            msg!("{0}", restriction.message());
+            return Err(restriction.to_error());
         }
+        // Memos are instructions of the transfer's transaction, so unlike the
+        // rules above this cannot be checked by the dry run
+        ctx.accounts.check_reason_memo()?;
+        ctx.accounts.sync_bond_holdings()?;
         msg!("Transfer hook executed successfully");
         Ok(())
     }
+
+    // Read-only dry run of the hook rules for wallets and UIs, takes the same
+    // accounts as `transfer_hook` and returns the restriction as return data
+    pub fn detect_transfer_restriction<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TransferHook<'info>>,
+        amount: u64
+    ) -> Result<TransferRestriction> {
+        let destination_balance = ctx.accounts.destination_token.amount.saturating_add(amount);
+        Ok(ctx.accounts.detect_restriction(amount, destination_balance, ctx.remaining_accounts)?.into())
+    }
+}
+
+// Only the mint's transfer hook authority may configure the hook's accounts
+pub fn check_hook_authority(mint: &AccountInfo, authority: &Pubkey) -> Result<()> {
+    let mint_data = mint.data.borrow();
+    let mint_with_extension = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+    let extension_data = mint_with_extension.get_extension::<TransferHookExtension>()?;
+
+    require!(
+        extension_data.authority == OptionalNonZeroPubkey::try_from(Some(*authority))?,
+        TransferError::InvalidHookAuthority
+    );
+    Ok(())
 }
 
 fn check_is_transferring(ctx: &Context<TransferHook>) -> Result<()> {
@@ -98,20 +562,27 @@ fn check_is_transferring(ctx: &Context<TransferHook>) -> Result<()> {
 }
 
 #[derive(Accounts)]
-#[instruction(_decimals: u8)]
 pub struct Initialize<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
+    // Created in `create_mint`, the extension set is only known at runtime
+    #[account(mut)]
+    pub mint_account: Signer<'info>,
+
+    // Required when the mint is created with a transfer fee, and only then
     #[account(
         init,
         payer = payer,
-        mint::decimals = _decimals,
-        mint::authority = payer,
-        extensions::transfer_hook::authority = payer,
-        extensions::transfer_hook::program_id = crate::ID
+        space = 8 + FeeConfig::INIT_SPACE,
+        seeds = [b"fee_config", mint_account.key().as_ref()],
+        bump
     )]
-    pub mint_account: InterfaceAccount<'info, Mint>,
+    pub fee_config: Option<Account<'info, FeeConfig>>,
+
+    /// CHECK: Freeze authority of the mint when it is created default-frozen
+    #[account(seeds = [b"freeze_authority", mint_account.key().as_ref()], bump)]
+    pub freeze_authority: UncheckedAccount<'info>,
 
     pub token_program: Program<'info, Token2022>,
     
@@ -119,6 +590,142 @@ pub struct Initialize<'info> {
 }
 
 impl<'info> Initialize<'info> {
+    pub fn create_mint(
+        &mut self,
+        decimals: u8,
+        transfer_fee: Option<TransferFeeParams>,
+        metadata: Option<&SecurityMetadata>,
+        default_frozen: bool,
+        bumps: &InitializeBumps
+    ) -> Result<()> {
+        let mut extensions = vec![ExtensionType::TransferHook];
+        if default_frozen {
+            extensions.push(ExtensionType::DefaultAccountState);
+        }
+        if transfer_fee.is_some() {
+            extensions.push(ExtensionType::TransferFeeConfig);
+        }
+        if metadata.is_some() {
+            extensions.push(ExtensionType::MetadataPointer);
+        }
+        let space = ExtensionType::try_calculate_account_len::<MintState>(&extensions)?;
+
+        // Token metadata is variable length and reallocated into the mint by
+        // Token-2022 later, but the rent for it has to be there up front
+        let metadata_space = match metadata {
+            Some(metadata) =>
+                metadata.to_token_metadata(self.payer.key(), self.mint_account.key())?.tlv_size_of()?,
+            None => 0,
+        };
+
+        system_program::create_account(
+            CpiContext::new(self.system_program.to_account_info(), CreateAccount {
+                from: self.payer.to_account_info(),
+                to: self.mint_account.to_account_info(),
+            }),
+            Rent::get()?.minimum_balance(space + metadata_space),
+            space as u64,
+            &self.token_program.key()
+        )?;
+
+        transfer_hook_initialize(
+            CpiContext::new(self.token_program.to_account_info(), TransferHookInitialize {
+                token_program_id: self.token_program.to_account_info(),
+                mint: self.mint_account.to_account_info(),
+            }),
+            Some(self.payer.key()),
+            Some(crate::ID)
+        )?;
+
+        // Without a fee the config would be left behind as an empty account
+        require!(transfer_fee.is_some() || self.fee_config.is_none(), FeeError::UnexpectedFeeConfig);
+        if let Some(fee) = transfer_fee {
+            require!(fee.basis_points <= 10_000, FeeError::InvalidBasisPoints);
+            let fee_config = self.fee_config.as_mut().ok_or(FeeError::MissingFeeConfig)?;
+            fee_config.set_inner(FeeConfig {
+                mint: self.mint_account.key(),
+                admin: self.payer.key(),
+                bump: bumps.fee_config.unwrap(),
+            });
+
+            // The fee config PDA can update the fee and withdraw withheld amounts
+            let fee_authority = fee_config.key();
+            transfer_fee_initialize(
+                CpiContext::new(self.token_program.to_account_info(), TransferFeeInitialize {
+                    token_program_id: self.token_program.to_account_info(),
+                    mint: self.mint_account.to_account_info(),
+                }),
+                Some(&fee_authority),
+                Some(&fee_authority),
+                fee.basis_points,
+                fee.maximum_fee
+            )?;
+        }
+
+        // New token accounts start frozen until `approve_holder` thaws them
+        let freeze_authority = if default_frozen {
+            default_account_state_initialize(
+                CpiContext::new(self.token_program.to_account_info(), DefaultAccountStateInitialize {
+                    token_program_id: self.token_program.to_account_info(),
+                    mint: self.mint_account.to_account_info(),
+                }),
+                &AccountState::Frozen
+            )?;
+            Some(self.freeze_authority.key())
+        } else {
+            None
+        };
+
+        if metadata.is_some() {
+            metadata_pointer_initialize(
+                CpiContext::new(self.token_program.to_account_info(), MetadataPointerInitialize {
+                    token_program_id: self.token_program.to_account_info(),
+                    mint: self.mint_account.to_account_info(),
+                }),
+                Some(self.payer.key()),
+                Some(self.mint_account.key())
+            )?;
+        }
+
+        token_2022_cpi::initialize_mint2(
+            CpiContext::new(self.token_program.to_account_info(), InitializeMint2 {
+                mint: self.mint_account.to_account_info(),
+            }),
+            decimals,
+            &self.payer.key(),
+            freeze_authority.as_ref()
+        )
+    }
+
+    // The payer is the metadata update authority, see `update_security_metadata`
+    pub fn initialize_metadata(&self, metadata: SecurityMetadata) -> Result<()> {
+        token_metadata_initialize(
+            CpiContext::new(self.token_program.to_account_info(), TokenMetadataInitialize {
+                token_program_id: self.token_program.to_account_info(),
+                metadata: self.mint_account.to_account_info(),
+                update_authority: self.payer.to_account_info(),
+                mint_authority: self.payer.to_account_info(),
+                mint: self.mint_account.to_account_info(),
+            }),
+            metadata.name.clone(),
+            metadata.symbol.clone(),
+            metadata.uri.clone()
+        )?;
+
+        for (key, value) in metadata.additional_fields() {
+            token_metadata_update_field(
+                CpiContext::new(self.token_program.to_account_info(), TokenMetadataUpdateField {
+                    token_program_id: self.token_program.to_account_info(),
+                    metadata: self.mint_account.to_account_info(),
+                    update_authority: self.payer.to_account_info(),
+                }),
+                Field::Key(key),
+                value
+            )?;
+        }
+        Ok(())
+    }
+
     pub fn check_mint_data(&self) -> Result<()> {
         let mint = &self.mint_account.to_account_info();
         let mint_data = mint.data.borrow();
@@ -138,6 +745,7 @@ impl<'info> Initialize<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(extra_account_metas: Vec<HookAccountConfig>)]
 pub struct InitializeExtraAccountMetaList<'info> {
     #[account(mut)]
     payer: Signer<'info>,
@@ -148,7 +756,7 @@ pub struct InitializeExtraAccountMetaList<'info> {
         seeds = [b"extra-account-metas", mint.key().as_ref()],
         bump,
         space = ExtraAccountMetaList::size_of(
-            InitializeExtraAccountMetaList::extra_account_metas()?.len()
+            InitializeExtraAccountMetaList::extra_account_metas(&extra_account_metas)?.len()
         )?,
         payer = payer
     )]
@@ -162,28 +770,205 @@ pub struct InitializeExtraAccountMetaList<'info> {
 
     pub system_program: Program<'info, System>,
 
-    #[account(init_if_needed, seeds = [b"white_list"], bump, payer = payer, space = 400)]
+    #[account(
+        init,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + Registry::INIT_SPACE
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(init, seeds = [b"white_list", mint.key().as_ref()], bump, payer = payer, space = 400)]
     pub white_list: Account<'info, WhiteList>,
 
+    #[account(
+        init,
+        seeds = [b"kyc_providers", mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + KycProviders::INIT_SPACE
+    )]
+    pub kyc_providers: Account<'info, KycProviders>,
+
+    #[account(
+        init,
+        seeds = [b"audit_log", mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + std::mem::size_of::<AuditLog>()
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        init,
+        seeds = [b"documents", mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + DocumentRegistry::INIT_SPACE
+    )]
+    pub documents: Account<'info, DocumentRegistry>,
+
+    // Present when the mint was created with a transfer fee
+    #[account(seeds = [b"fee_config", mint.key().as_ref()], bump = fee_config.bump)]
+    pub fee_config: Option<Account<'info, FeeConfig>>,
+
 }
 
-// Define extra account metas to store on extra_account_meta_list account
+// Define extra account metas to store on extra_account_meta_list account: the
+// built-in accounts read by `TransferHook`, followed by the issuer-configured ones
+// which reach the hook as remaining accounts. Changing the built-ins changes the
+// layout of every existing list, see `update_extra_account_meta_list`.
 impl<'info> InitializeExtraAccountMetaList<'info> {
-    pub fn extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
-        
-        Ok(
-            vec![
-                ExtraAccountMeta::new_with_seeds(
-                    &[
-                        Seed::Literal {
-                            bytes: "white_list".as_bytes().to_vec(),
-                        },
-                    ],
-                    false, // is_signer
-                    true // is_writable
-                )?
-            ]
-        )
+    pub fn extra_account_metas(
+        configured: &[HookAccountConfig]
+    ) -> Result<Vec<ExtraAccountMeta>> {
+        let mut extra_account_metas = vec![
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: "registry".as_bytes().to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                ],
+                false, // is_signer
+                false // is_writable
+            )?,
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: "white_list".as_bytes().to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                ],
+                false, // is_signer
+                true // is_writable
+            )?,
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: "kyc_providers".as_bytes().to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                ],
+                false, // is_signer
+                false // is_writable
+            )?,
+            // attestation of the destination owner (token account owner at offset 32)
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: "attestation".as_bytes().to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                    Seed::AccountData {
+                        account_index: 2,
+                        data_index: 32,
+                        length: 32,
+                    },
+                ],
+                false, // is_signer
+                false // is_writable
+            )?,
+            // cached signed approval for the destination owner
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: "approval".as_bytes().to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                    Seed::AccountData {
+                        account_index: 2,
+                        data_index: 32,
+                        length: 32,
+                    },
+                ],
+                false, // is_signer
+                false // is_writable
+            )?,
+            // bond terms, when the mint is a bond
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: "bond".as_bytes().to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                ],
+                false, // is_signer
+                false // is_writable
+            )?,
+            // coupon holdings of the source and destination token accounts
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: "bond_holding".as_bytes().to_vec(),
+                    },
+                    Seed::AccountKey { index: 0 },
+                ],
+                false, // is_signer
+                true // is_writable
+            )?,
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: "bond_holding".as_bytes().to_vec(),
+                    },
+                    Seed::AccountKey { index: 2 },
+                ],
+                false, // is_signer
+                true // is_writable
+            )?,
+            // per-holder balance caps
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: "holding_limits".as_bytes().to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                ],
+                false, // is_signer
+                false // is_writable
+            )?,
+            // compliance rule modules consulted after the built-in rules
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: "rule_modules".as_bytes().to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                ],
+                false, // is_signer
+                false // is_writable
+            )?,
+            // reason codes required in transfer memos
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: "memo_policy".as_bytes().to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                ],
+                false, // is_signer
+                false // is_writable
+            )?,
+            ExtraAccountMeta::new_with_pubkey(&sysvar::instructions::ID, false, false)?,
+            // offering config, holds the compliance key approvals must be signed by
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: "config".as_bytes().to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                ],
+                false, // is_signer
+                false // is_writable
+            )?
+        ];
+
+        for config in configured {
+            extra_account_metas.push(config.to_extra_account_meta()?);
+        }
+        Ok(extra_account_metas)
     }
 
 
@@ -191,7 +976,7 @@ impl<'info> InitializeExtraAccountMetaList<'info> {
 
 #[derive(Accounts)]
 pub struct TransferHook<'info> {
-    #[account(token::mint = mint, token::authority = owner)]
+    #[account(token::mint = mint)]
     pub source_token: InterfaceAccount<'info, TokenAccount>,
 
     pub mint: InterfaceAccount<'info, Mint>,
@@ -199,13 +984,73 @@ pub struct TransferHook<'info> {
     #[account(token::mint = mint)]
     pub destination_token: InterfaceAccount<'info, TokenAccount>,
 
-    /// CHECK
+    /// CHECK: Transfer authority: the source owner, its delegate (escrows, vesting
+    /// beneficiaries) or the mint's permanent delegate. Rules are applied to the
+    /// token account owners, not to this account.
+    #[account(
+        constraint = is_transfer_authority(&source_token, &mint.to_account_info(), owner.key)? @
+            TransferError::InvalidTransferAuthority
+    )]
     pub owner: UncheckedAccount<'info>,
 
     /// CHECK
     #[account(seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
     pub extra_account_meta_list: UncheckedAccount<'info>,
 
-    #[account(seeds = [b"white_list"], bump)]
+    #[account(
+        has_one = white_list,
+        has_one = kyc_providers,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(seeds = [b"white_list", mint.key().as_ref()], bump)]
     pub white_list: Account<'info, WhiteList>,
+
+    #[account(seeds = [b"kyc_providers", mint.key().as_ref()], bump)]
+    pub kyc_providers: Account<'info, KycProviders>,
+
+    /// CHECK: May be uninitialized, validated in `has_valid_attestation`
+    #[account(seeds = [b"attestation", mint.key().as_ref(), destination_token.owner.as_ref()], bump)]
+    pub attestation: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, validated in `has_valid_approval`
+    #[account(
+        seeds = [b"approval", mint.key().as_ref(), destination_token.owner.as_ref()],
+        bump
+    )]
+    pub approval: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, read in `sync_bond_holdings`
+    #[account(seeds = [b"bond", mint.key().as_ref()], bump)]
+    pub bond: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, updated in `sync_bond_holdings`
+    #[account(mut, seeds = [b"bond_holding", source_token.key().as_ref()], bump)]
+    pub source_holding: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, updated in `sync_bond_holdings`
+    #[account(mut, seeds = [b"bond_holding", destination_token.key().as_ref()], bump)]
+    pub destination_holding: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, read in `exceeds_holding_limits`
+    #[account(seeds = [b"holding_limits", mint.key().as_ref()], bump)]
+    pub holding_limits: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, read in `passes_rule_modules`
+    #[account(seeds = [b"rule_modules", mint.key().as_ref()], bump)]
+    pub rule_modules: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, read in `check_reason_memo`
+    #[account(seeds = [b"memo_policy", mint.key().as_ref()], bump)]
+    pub memo_policy: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar, used to find the memo of the transfer
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// CHECK: May be uninitialized, read in `has_valid_approval`
+    #[account(seeds = [b"config", mint.key().as_ref()], bump)]
+    pub config: UncheckedAccount<'info>,
 }
\ No newline at end of file
@@ -0,0 +1,98 @@
+use anchor_lang::{ prelude::*, Discriminator };
+use anchor_lang::solana_program::{
+    instruction::{ get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT },
+    sysvar::instructions::{ load_current_index_checked, load_instruction_at_checked },
+};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{ transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions },
+    instruction::TokenInstruction,
+    state::Mint as MintState,
+};
+use crate::errors::MarketError;
+use crate::state::market::OrderSide;
+
+// Position of `security_vault` in the accounts of `post_order`
+const POST_ORDER_SECURITY_VAULT: usize = 8;
+
+// Transfers of the security cannot be made by CPI from this program (the hook
+// would re-enter it), so market instructions move the security with a plain
+// `transfer_checked` placed right after them in the same transaction. This
+// checks that instruction; if it then fails, the whole transaction is reverted.
+// The calling instruction must be top level and identified by `discriminator`:
+// invoked by CPI, several calls could otherwise be paired with the same transfer.
+pub fn require_next_transfer(
+    instructions_sysvar: &AccountInfo,
+    discriminator: &[u8],
+    source: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    amount: u64
+) -> Result<()> {
+    require!(get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT, MarketError::NotTopLevel);
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    let current = load_instruction_at_checked(current_index as usize, instructions_sysvar)?;
+    require!(
+        current.program_id == crate::ID && current.data.starts_with(discriminator),
+        MarketError::NotTopLevel
+    );
+
+    let ix = load_instruction_at_checked((current_index as usize) + 1, instructions_sysvar).map_err(
+        |_| MarketError::MissingSettlementTransfer
+    )?;
+    require_keys_eq!(ix.program_id, spl_token_2022::ID, MarketError::MissingSettlementTransfer);
+
+    let transfer_amount = match TokenInstruction::unpack(&ix.data) {
+        Ok(TokenInstruction::TransferChecked { amount, .. }) => amount,
+        _ => return err!(MarketError::MissingSettlementTransfer),
+    };
+
+    // transfer_checked accounts: [source, mint, destination, authority, ..]
+    require!(
+        ix.accounts.len() >= 4 &&
+            ix.accounts[0].pubkey == *source &&
+            ix.accounts[1].pubkey == *mint &&
+            ix.accounts[2].pubkey == *destination &&
+            transfer_amount == amount,
+        MarketError::MissingSettlementTransfer
+    );
+    Ok(())
+}
+
+// Order vaults are owned by their order PDA, which is never whitelisted or
+// attested. A vault may still receive the security as the escrow transfer of
+// an ask: the `post_order` right before this transfer has checked, through
+// `require_next_transfer`, that it moves the ask into the vault of its order.
+pub fn is_order_escrow(instructions_sysvar: &AccountInfo, destination: &Pubkey) -> Result<bool> {
+    let current_index = load_current_index_checked(instructions_sysvar)? as usize;
+    let Some(previous_index) = current_index.checked_sub(1) else {
+        return Ok(false);
+    };
+    let ix = load_instruction_at_checked(previous_index, instructions_sysvar)?;
+    let discriminator = crate::instruction::PostOrder::DISCRIMINATOR;
+    if ix.program_id != crate::ID || !ix.data.starts_with(&discriminator) {
+        return Ok(false);
+    }
+
+    let Ok(post_order) = crate::instruction::PostOrder::try_from_slice(&ix.data[discriminator.len()..]) else {
+        return Ok(false);
+    };
+    Ok(
+        post_order.side == OrderSide::Ask &&
+            ix.accounts.get(POST_ORDER_SECURITY_VAULT).is_some_and(|meta| meta.pubkey == *destination)
+    )
+}
+
+// Amount the destination receives once the mint's transfer fee, if any, is withheld
+pub fn net_of_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let mint_data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+    let fee = match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(fee_config) =>
+            fee_config
+                .calculate_epoch_fee(Clock::get()?.epoch, amount)
+                .ok_or(MarketError::MathOverflow)?,
+        Err(_) => 0,
+    };
+    Ok(amount - fee)
+}
@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+// Pending request of a wallet to have its token account whitelisted. Closed,
+// with its rent returned to the applicant, once approved or rejected.
+#[account]
+#[derive(InitSpace)]
+pub struct WhitelistApplication {
+    pub mint: Pubkey,
+    pub applicant: Pubkey,
+    pub token_account: Pubkey,
+    // Hash of the off-chain KYC document submitted with the request
+    pub document_hash: [u8; 32],
+    pub created_at: i64,
+    pub bump: u8,
+}
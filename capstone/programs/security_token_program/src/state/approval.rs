@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+// Cached result of an ed25519 approval signed by the compliance key
+#[account]
+#[derive(InitSpace)]
+pub struct SignedApproval {
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    pub expires_at: i64,
+    // Compliance key that signed the approval, rotating the key invalidates it
+    pub signer: Pubkey,
+    pub bump: u8,
+}
+
+impl SignedApproval {
+    pub fn is_valid(&self, wallet: &Pubkey, mint: &Pubkey, compliance_key: &Pubkey, now: i64) -> bool {
+        self.wallet == *wallet &&
+            self.mint == *mint &&
+            self.signer == *compliance_key &&
+            self.expires_at > now
+    }
+}
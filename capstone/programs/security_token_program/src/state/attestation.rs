@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_KYC_PROVIDERS: usize = 10;
+
+#[account]
+#[derive(InitSpace)]
+pub struct KycProviders {
+    pub authority: Pubkey,
+    #[max_len(MAX_KYC_PROVIDERS)]
+    pub providers: Vec<Pubkey>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Attestation {
+    pub investor: Pubkey,
+    pub mint: Pubkey,
+    pub provider: Pubkey,
+    pub level: u8,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl Attestation {
+    pub fn is_valid(&self, investor: &Pubkey, providers: &KycProviders, now: i64) -> bool {
+        self.investor == *investor &&
+            self.expires_at > now &&
+            providers.providers.contains(&self.provider)
+    }
+}
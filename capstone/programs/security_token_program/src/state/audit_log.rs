@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+
+// Kept small enough for the account to be created by CPI (10 KiB)
+pub const AUDIT_LOG_CAPACITY: usize = 64;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum AuditAction {
+    WhitelistAdd = 0,
+    WhitelistRemove = 1,
+    PriceUpdate = 2,
+    StatusChange = 3,
+    KycProviderAdd = 4,
+    KycProviderRemove = 5,
+    HolderApproved = 6,
+    ApplicationRejected = 7,
+    TrancheAdd = 8,
+    AttestationIssue = 9,
+    AttestationRevoke = 10,
+    ComplianceKeyChange = 11,
+    TransferFeeUpdate = 12,
+    VestingRevoke = 13,
+    HoldingLimitsUpdate = 14,
+    RuleModulesUpdate = 15,
+    MemoPolicyUpdate = 16,
+    HolderRevoked = 17,
+    InvestorClassUpdate = 18,
+    EscrowConfigUpdate = 19,
+    OfferingCancel = 20,
+    MilestoneAchieved = 21,
+    NavPublisherChange = 22,
+    NavStrike = 23,
+    FundCashWithdraw = 24,
+    FeeWithdraw = 25,
+    MetadataUpdate = 26,
+    HookAccountsUpdate = 27,
+    BondCreate = 28,
+    MarketCreate = 29,
+    DocumentSet = 30,
+    DocumentRemove = 31,
+    ConfigInit = 32,
+}
+
+#[zero_copy]
+pub struct AuditEntry {
+    pub slot: u64,
+    pub actor: Pubkey,
+    pub target: Pubkey,
+    // Hash of the off-chain reason document, supplied by the actor
+    pub reason_hash: [u8; 32],
+    pub action: u8,
+    pub _padding: [u8; 7],
+}
+
+// Fixed-size ring buffer of admin actions for a mint, read directly from
+// account state. Once full, the oldest entry is overwritten.
+#[account(zero_copy)]
+pub struct AuditLog {
+    pub mint: Pubkey,
+    // Total number of entries ever appended; the next one goes to `total % capacity`
+    pub total: u64,
+    pub entries: [AuditEntry; AUDIT_LOG_CAPACITY],
+}
+
+impl AuditLog {
+    pub fn append(&mut self, actor: Pubkey, action: AuditAction, target: Pubkey, reason_hash: [u8; 32]) -> Result<()> {
+        let index = (self.total as usize) % AUDIT_LOG_CAPACITY;
+        self.entries[index] = AuditEntry {
+            slot: Clock::get()?.slot,
+            actor,
+            target,
+            reason_hash,
+            action: action as u8,
+            _padding: [0; 7],
+        };
+        self.total += 1;
+        Ok(())
+    }
+}
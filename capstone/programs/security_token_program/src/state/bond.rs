@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+pub const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
+// Scale of `coupon_index`, coupons per token base unit are fractional
+pub const COUPON_INDEX_PRECISION: u128 = 1_000_000_000_000;
+
+// Bond terms for a security mint. Coupons fall due every `coupon_interval` from
+// `start_ts` until `maturity_ts`; amounts are in payment base units per security
+// base unit.
+#[account]
+#[derive(InitSpace)]
+pub struct Bond {
+    pub mint: Pubkey,
+    pub payment_mint: Pubkey,
+    pub face_value: u64,
+    // Annual coupon rate in basis points of the face value
+    pub coupon_rate_bps: u16,
+    pub coupon_interval: i64,
+    pub start_ts: i64,
+    pub maturity_ts: i64,
+    pub periods_accrued: u32,
+    // Coupons accrued per security base unit, scaled by COUPON_INDEX_PRECISION
+    pub coupon_index: u128,
+    pub bump: u8,
+}
+
+impl Bond {
+    pub fn total_periods(&self) -> u32 {
+        ((self.maturity_ts - self.start_ts) / self.coupon_interval) as u32
+    }
+
+    // Coupon periods fully elapsed at `now`
+    pub fn elapsed_periods(&self, now: i64) -> u32 {
+        if now <= self.start_ts {
+            return 0;
+        }
+        let elapsed = ((now - self.start_ts) / self.coupon_interval) as u32;
+        elapsed.min(self.total_periods())
+    }
+
+    pub fn coupon_per_period(&self) -> Option<u128> {
+        (self.face_value as u128)
+            .checked_mul(self.coupon_rate_bps as u128)?
+            .checked_mul(self.coupon_interval as u128)?
+            .checked_mul(COUPON_INDEX_PRECISION)?
+            .checked_div(10_000 * (SECONDS_PER_YEAR as u128))
+    }
+}
+
+// Coupon checkpoint of a single token account. `balance` is the balance last
+// seen by the program: the hook refreshes it on every transfer, the program's
+// mints and burns after moving the balance and claims before paying, so
+// coupons are only earned on tokens held while the index moved.
+#[account]
+#[derive(InitSpace)]
+pub struct BondHolding {
+    pub token_account: Pubkey,
+    pub balance: u64,
+    pub index_checkpoint: u128,
+    pub accrued: u64,
+    pub bump: u8,
+}
+
+impl BondHolding {
+    // Credits the coupons earned since the last checkpoint and records the new balance
+    pub fn sync(&mut self, coupon_index: u128, balance: u64) -> Option<()> {
+        let earned = (self.balance as u128)
+            .checked_mul(coupon_index.checked_sub(self.index_checkpoint)?)?
+            .checked_div(COUPON_INDEX_PRECISION)?;
+        self.accrued = self.accrued.checked_add(u64::try_from(earned).ok()?)?;
+        self.index_checkpoint = coupon_index;
+        self.balance = balance;
+        Some(())
+    }
+}
@@ -1,8 +1,61 @@
 use anchor_lang::prelude::*;
 
+pub const MAX_TRANCHES: usize = 8;
+
 #[account]
+#[derive(InitSpace)]
 pub struct Config {
     pub owner: Pubkey,
-    pub price: u64,
+    pub mint: Pubkey,
+    // Signs off-chain whitelist approvals, see `submit_approval`
+    pub compliance_key: Pubkey,
     pub max_supply: u64,
-}
\ No newline at end of file
+    pub total_sold: u64,
+    #[max_len(MAX_TRANCHES)]
+    pub tranches: Vec<Tranche>,
+    // Escrowed-subscription mode: payments wait in a vault owned by this
+    // config until the offering is finalized or refunded
+    pub escrow_payments: bool,
+    pub payment_mint: Pubkey,
+    pub soft_cap: u64,
+    pub close_ts: i64,
+    pub total_raised: u64,
+    pub status: SaleStatus,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum SaleStatus {
+    Open,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+// A sale round (seed, private, public...) with its own price and allocation.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Tranche {
+    pub price: u64,
+    pub cap: u64,
+    pub sold: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub whitelist_class: Option<u8>,
+}
+
+impl Tranche {
+    pub fn is_open(&self, now: i64) -> bool {
+        self.start_ts <= now && now < self.end_ts
+    }
+
+    pub fn accepts(&self, investor_class: Option<u8>) -> bool {
+        match self.whitelist_class {
+            Some(class) => investor_class == Some(class),
+            None => true,
+        }
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.cap.saturating_sub(self.sold)
+    }
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+
+pub const MAX_DOCUMENT_NAME_LEN: usize = 32;
+pub const MAX_DOCUMENT_URI_LEN: usize = 200;
+
+// One version of a named document, in its own account numbered by the registry
+// so the history is never loaded as a whole. Versions are never closed: setting
+// a name again or removing it ends the version in force at that slot.
+#[account]
+#[derive(PartialEq, Eq, Debug)]
+pub struct Document {
+    pub mint: Pubkey,
+    pub index: u32,
+    pub name: String,
+    pub uri: String,
+    pub content_hash: [u8; 32],
+    pub timestamp: i64,
+    pub from_slot: u64,
+    // None while the version is in force
+    pub until_slot: Option<u64>,
+    pub bump: u8,
+}
+
+impl Document {
+    pub fn space(name: &str, uri: &str) -> usize {
+        8 + 32 + 4 + 4 + name.len() + 4 + uri.len() + 32 + 8 + 8 + 1 + 8 + 1
+    }
+
+    pub fn in_force_at(&self, slot: u64) -> bool {
+        self.from_slot <= slot && self.until_slot.map_or(true, |until| slot < until)
+    }
+}
+
+// Points a document name at its version in force
+#[account]
+#[derive(InitSpace)]
+pub struct DocumentHead {
+    pub mint: Pubkey,
+    pub current: Option<u32>,
+    pub bump: u8,
+}
+
+impl DocumentHead {
+    // Names are hashed so that any name can be a seed
+    pub fn seed(name: &str) -> [u8; 32] {
+        hash(name.as_bytes()).to_bytes()
+    }
+}
+
+// ERC-1643 style document history of a mint. The versions are the `Document`
+// accounts numbered `0..versions`, in the order they were set.
+#[account]
+#[derive(InitSpace)]
+pub struct DocumentRegistry {
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub versions: u32,
+}
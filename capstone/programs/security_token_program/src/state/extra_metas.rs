@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use spl_tlv_account_resolution::{ account::ExtraAccountMeta, seeds::Seed };
+
+// Offset of the owner field in a token account
+const TOKEN_ACCOUNT_OWNER_OFFSET: u8 = 32;
+
+// Seed of a PDA resolved by Token-2022 from the transfer's own inputs. Account
+// indexes refer to the execute accounts list: source (0), mint (1),
+// destination (2), owner (3), extra account meta list (4), then extra accounts.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum HookSeed {
+    Literal {
+        bytes: Vec<u8>,
+    },
+    Mint,
+    SourceOwner,
+    DestinationOwner,
+    // Little-endian bytes of the transfer amount
+    Amount,
+    AccountKey {
+        index: u8,
+    },
+    AccountData {
+        account_index: u8,
+        data_index: u8,
+        length: u8,
+    },
+    InstructionData {
+        index: u8,
+        length: u8,
+    },
+}
+
+impl From<&HookSeed> for Seed {
+    fn from(seed: &HookSeed) -> Self {
+        match seed {
+            HookSeed::Literal { bytes } => Seed::Literal { bytes: bytes.clone() },
+            HookSeed::Mint => Seed::AccountKey { index: 1 },
+            HookSeed::SourceOwner =>
+                Seed::AccountData {
+                    account_index: 0,
+                    data_index: TOKEN_ACCOUNT_OWNER_OFFSET,
+                    length: 32,
+                },
+            HookSeed::DestinationOwner =>
+                Seed::AccountData {
+                    account_index: 2,
+                    data_index: TOKEN_ACCOUNT_OWNER_OFFSET,
+                    length: 32,
+                },
+            // execute data: 8-byte discriminator followed by the u64 amount
+            HookSeed::Amount => Seed::InstructionData { index: 8, length: 8 },
+            HookSeed::AccountKey { index } => Seed::AccountKey { index: *index },
+            HookSeed::AccountData { account_index, data_index, length } =>
+                Seed::AccountData {
+                    account_index: *account_index,
+                    data_index: *data_index,
+                    length: *length,
+                },
+            HookSeed::InstructionData { index, length } =>
+                Seed::InstructionData { index: *index, length: *length },
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum HookAccountAddress {
+    Fixed {
+        address: Pubkey,
+    },
+    // PDA of this program
+    Pda {
+        seeds: Vec<HookSeed>,
+    },
+    // PDA of the program found at `program_index` in the accounts list
+    ExternalPda {
+        program_index: u8,
+        seeds: Vec<HookSeed>,
+    },
+}
+
+// Issuer-configured account appended after the built-in hook accounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct HookAccountConfig {
+    pub address: HookAccountAddress,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl HookAccountConfig {
+    pub fn to_extra_account_meta(&self) -> Result<ExtraAccountMeta> {
+        let meta = match &self.address {
+            HookAccountAddress::Fixed { address } =>
+                ExtraAccountMeta::new_with_pubkey(address, self.is_signer, self.is_writable)?,
+            HookAccountAddress::Pda { seeds } => {
+                let seeds: Vec<Seed> = seeds.iter().map(Seed::from).collect();
+                ExtraAccountMeta::new_with_seeds(&seeds, self.is_signer, self.is_writable)?
+            }
+            HookAccountAddress::ExternalPda { program_index, seeds } => {
+                let seeds: Vec<Seed> = seeds.iter().map(Seed::from).collect();
+                ExtraAccountMeta::new_external_pda_with_seeds(
+                    *program_index,
+                    &seeds,
+                    self.is_signer,
+                    self.is_writable
+                )?
+            }
+        };
+        Ok(meta)
+    }
+}
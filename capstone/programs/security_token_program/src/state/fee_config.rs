@@ -0,0 +1,17 @@
+use anchor_lang::prelude::*;
+
+// Transfer-agent fee charged by the Token-2022 transfer-fee extension
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct TransferFeeParams {
+    pub basis_points: u16,
+    pub maximum_fee: u64,
+}
+
+// Transfer fee config and withdraw-withheld authority of a security mint
+#[account]
+#[derive(InitSpace)]
+pub struct FeeConfig {
+    pub mint: Pubkey,
+    pub admin: Pubkey,
+    pub bump: u8,
+}
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+
+// Scale of a NAV: payment base units per share base unit
+pub const NAV_PRECISION: u128 = 1_000_000_000;
+
+// Dealing terms of a fund share mint. Requests placed during `current_epoch`
+// settle at the NAV struck for that epoch. Subscription payments and redemption
+// liquidity sit in the fund's payment vault.
+#[account]
+#[derive(InitSpace)]
+pub struct Fund {
+    pub mint: Pubkey,
+    pub payment_mint: Pubkey,
+    pub nav_publisher: Pubkey,
+    pub current_epoch: u64,
+    // Payment received for the current epoch
+    pub pending_subscriptions: u64,
+    // Shares requested for redemption in the current epoch
+    pub pending_redemptions: u64,
+    // Payment owed to struck requests not settled yet: redemption proceeds, and
+    // subscription payments that are refunded if the shares breach holding limits
+    pub reserved: u64,
+    pub bump: u8,
+}
+
+pub fn shares_for(payment: u64, nav: u64) -> Option<u64> {
+    ((payment as u128) * NAV_PRECISION).checked_div(nav as u128)?.try_into().ok()
+}
+
+pub fn payment_for(shares: u64, nav: u64) -> Option<u64> {
+    ((shares as u128) * (nav as u128) / NAV_PRECISION).try_into().ok()
+}
+
+// Shares out of `redemptions` that `available` liquidity pays for at `nav`, pro
+// rata when it falls short, and the payment to reserve for them
+pub fn redemption_fill(redemptions: u64, nav: u64, available: u64) -> Option<(u64, u64)> {
+    let owed = payment_for(redemptions, nav)?;
+    if owed <= available {
+        return Some((redemptions, owed));
+    }
+    let redeemable = ((redemptions as u128) * (available as u128) / (owed as u128)) as u64;
+    Some((redeemable, payment_for(redeemable, nav)?))
+}
+
+// NAV struck for an epoch, along with the share of its redemptions the vault
+// could pay for at the time
+#[account]
+#[derive(InitSpace)]
+pub struct NavRecord {
+    pub fund: Pubkey,
+    pub epoch: u64,
+    pub nav: u64,
+    pub struck_at: i64,
+    pub subscriptions: u64,
+    pub redemptions: u64,
+    // Equal to `redemptions` unless liquidity was short
+    pub redeemable: u64,
+    // Payment still reserved for the redemptions of this strike, and the shares
+    // of the ones not settled yet. The last settlement releases what rounding left.
+    pub reserved: u64,
+    pub unsettled: u64,
+    pub bump: u8,
+}
+
+impl NavRecord {
+    // Pro-rata part of a redemption request filled at this strike, rounded down
+    pub fn filled_shares(&self, shares: u64) -> u64 {
+        if self.redeemable >= self.redemptions {
+            return shares;
+        }
+        ((shares as u128) * (self.redeemable as u128) / (self.redemptions as u128)) as u64
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum FundRequestKind {
+    // `amount` is in payment base units
+    Subscription,
+    // `amount` is in share base units
+    Redemption,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct FundRequest {
+    pub fund: Pubkey,
+    pub investor: Pubkey,
+    pub epoch: u64,
+    pub kind: FundRequestKind,
+    pub amount: u64,
+    // Receives the shares of a subscription, delivers those of a redemption
+    pub token_account: Pubkey,
+    // Pays a subscription, receives the proceeds of a redemption
+    pub payment_account: Pubkey,
+    pub bump: u8,
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_LIMIT_EXEMPTIONS: usize = 8;
+
+// Per-holder caps on a mint, checked against the balance after a mint or
+// transfer. Zero disables a limit. Exempt keys are token accounts (treasury)
+// or their owners (issuer wallets).
+#[account]
+#[derive(InitSpace)]
+pub struct HoldingLimits {
+    pub mint: Pubkey,
+    pub max_balance: u64,
+    // Maximum share of the supply held by one account, in basis points
+    pub max_supply_bps: u16,
+    // Supply `max_supply_bps` is measured against, fixed by the issuer (usually
+    // the authorised supply) so issuance and burns don't move the limit
+    pub supply_basis: u64,
+    #[max_len(MAX_LIMIT_EXEMPTIONS)]
+    pub exempt: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl HoldingLimits {
+    pub fn is_exempt(&self, token_account: &Pubkey, owner: &Pubkey) -> bool {
+        self.exempt.contains(token_account) || self.exempt.contains(owner)
+    }
+
+    pub fn allows(&self, balance: u64) -> bool {
+        if self.max_balance > 0 && balance > self.max_balance {
+            return false;
+        }
+        if self.max_supply_bps > 0 {
+            return (balance as u128) * 10_000 <= (self.supply_basis as u128) * (self.max_supply_bps as u128);
+        }
+        true
+    }
+}
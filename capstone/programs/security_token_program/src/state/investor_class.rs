@@ -0,0 +1,9 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct InvestorClass {
+    pub investor: Pubkey,
+    pub class: u8,
+    pub bump: u8,
+}
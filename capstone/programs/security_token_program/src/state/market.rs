@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_MARKET_CLASSES: usize = 8;
+
+// Secondary market for a security mint, quoted in a single payment mint
+#[account]
+#[derive(InitSpace)]
+pub struct Market {
+    pub mint: Pubkey,
+    pub payment_mint: Pubkey,
+    // Offering config, used to look up investor classes
+    pub config: Pubkey,
+    // No orders can be posted or filled before this time
+    pub lockup_until: i64,
+    // Investor classes allowed to trade, empty allows every holder
+    #[max_len(MAX_MARKET_CLASSES)]
+    pub allowed_classes: Vec<u8>,
+    pub bump: u8,
+}
+
+impl Market {
+    pub fn allows_class(&self, class: Option<u8>) -> bool {
+        self.allowed_classes.is_empty() ||
+            class.is_some_and(|class| self.allowed_classes.contains(&class))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum OrderSide {
+    // Buy the security, payment is escrowed
+    Bid,
+    // Sell the security, tokens are escrowed
+    Ask,
+}
+
+// Resting limit order. `price` is in payment base units per security base unit.
+#[account]
+#[derive(InitSpace)]
+pub struct Order {
+    pub market: Pubkey,
+    pub maker: Pubkey,
+    pub nonce: u64,
+    pub side: OrderSide,
+    pub price: u64,
+    // Amount of the security still open
+    pub remaining: u64,
+    // Receives the security of a bid, source of an ask
+    pub maker_token_account: Pubkey,
+    // Receives the payment of an ask, source of a bid
+    pub maker_payment_account: Pubkey,
+    pub bump: u8,
+}
+
+impl Order {
+    pub fn cost(&self, amount: u64) -> Option<u64> {
+        self.price.checked_mul(amount)
+    }
+}
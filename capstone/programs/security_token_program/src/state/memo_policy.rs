@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_REASON_CODES: usize = 16;
+pub const MAX_REASON_CODE_LEN: usize = 16;
+
+// Separates the reason code of a transfer memo from free-form details, as in "SALE:trade 1042"
+pub const REASON_CODE_SEPARATOR: char = ':';
+
+// Reason codes defined by the issuer, e.g. "SALE", "GIFT" or "INHERITANCE".
+// While `required` is set, every transfer of the mint must be preceded in its
+// transaction by a memo starting with one of them.
+#[account]
+#[derive(InitSpace)]
+pub struct MemoPolicy {
+    pub mint: Pubkey,
+    pub required: bool,
+    #[max_len(MAX_REASON_CODES, MAX_REASON_CODE_LEN)]
+    pub reason_codes: Vec<String>,
+    pub bump: u8,
+}
+
+impl MemoPolicy {
+    // Reason code declared by a memo, if it is one of the issuer's codes
+    pub fn reason_code(&self, memo: &[u8]) -> Option<&str> {
+        let memo = std::str::from_utf8(memo).ok()?;
+        let code = memo.split(REASON_CODE_SEPARATOR).next()?.trim();
+        self.reason_codes
+            .iter()
+            .find(|reason_code| reason_code.as_str() == code)
+            .map(String::as_str)
+    }
+}
@@ -1,6 +1,60 @@
 // state/mod.rs
+pub mod application;
+pub use application::*;
+
+pub mod approval;
+pub use approval::*;
+
+pub mod attestation;
+pub use attestation::*;
+
+pub mod audit_log;
+pub use audit_log::*;
+
+pub mod bond;
+pub use bond::*;
+
 pub mod config;
 pub use config::*;
 
+pub mod documents;
+pub use documents::*;
+
+pub mod extra_metas;
+pub use extra_metas::*;
+
+pub mod fee_config;
+pub use fee_config::*;
+
+pub mod fund;
+pub use fund::*;
+
+pub mod holding_limits;
+pub use holding_limits::*;
+
+pub mod investor_class;
+pub use investor_class::*;
+
 pub mod whitelist;
-pub use whitelist::*;
\ No newline at end of file
+pub use whitelist::*;
+
+pub mod market;
+pub use market::*;
+
+pub mod memo_policy;
+pub use memo_policy::*;
+
+pub mod registry;
+pub use registry::*;
+
+pub mod rule_modules;
+pub use rule_modules::*;
+
+pub mod security_metadata;
+pub use security_metadata::*;
+
+pub mod subscription;
+pub use subscription::*;
+
+pub mod vesting;
+pub use vesting::*;
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum SecurityStatus {
+    // Being configured, no sales or transfers yet
+    Draft,
+    Active,
+    // Trading halt, sales and transfers are rejected
+    Halted,
+    // Terminal, the instrument has matured
+    Matured,
+}
+
+impl SecurityStatus {
+    pub fn can_transition_to(&self, next: SecurityStatus) -> bool {
+        use SecurityStatus::*;
+        matches!(
+            (self, next),
+            (Draft, Active) | (Active, Halted) | (Halted, Active) | (Active, Matured) | (Halted, Matured)
+        )
+    }
+
+    // Terms can still be set up or amended; halted and matured securities are left as they are
+    pub fn allows_changes(&self) -> bool {
+        matches!(self, SecurityStatus::Draft | SecurityStatus::Active)
+    }
+}
+
+// Per-mint entry tying a hooked mint to its admin, status and state accounts.
+// Instructions find the white list, provider list and config through it.
+#[account]
+#[derive(InitSpace)]
+pub struct Registry {
+    pub mint: Pubkey,
+    pub admin: Pubkey,
+    pub status: SecurityStatus,
+    pub extra_account_meta_list: Pubkey,
+    pub white_list: Pubkey,
+    pub kyc_providers: Pubkey,
+    // Default until `initialize_config` runs
+    pub config: Pubkey,
+    // Default when the mint has no transfer fee
+    pub fee_config: Pubkey,
+    pub bump: u8,
+}
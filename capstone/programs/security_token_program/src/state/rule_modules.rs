@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_RULE_MODULES: usize = 8;
+
+// Anchor discriminator of `check_transfer(amount: u64)`, sha256("global:check_transfer")[..8]
+pub const CHECK_TRANSFER_DISCRIMINATOR: [u8; 8] = [181, 98, 3, 219, 143, 70, 25, 215];
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct RuleModule {
+    pub program: Pubkey,
+    // Accounts the module reads, passed after the transfer accounts
+    pub account_count: u8,
+}
+
+// Compliance programs consulted by the hook, in order, after the built-in rules.
+// Each module takes its program account followed by `account_count` accounts
+// from the start of the issuer-configured hook accounts.
+#[account]
+#[derive(InitSpace)]
+pub struct RuleModules {
+    pub mint: Pubkey,
+    #[max_len(MAX_RULE_MODULES)]
+    pub modules: Vec<RuleModule>,
+    pub bump: u8,
+}
+
+impl RuleModules {
+    // Configured accounts consumed by the modules, program accounts included
+    pub fn account_len(&self) -> usize {
+        self.modules
+            .iter()
+            .map(|module| 1 + (module.account_count as usize))
+            .sum()
+    }
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    spl_pod::optional_keys::OptionalNonZeroPubkey,
+    spl_token_metadata_interface::state::TokenMetadata,
+};
+
+pub const ISIN_KEY: &str = "isin";
+pub const CUSIP_KEY: &str = "cusip";
+pub const DOCUMENT_HASH_KEY: &str = "document_hash";
+
+// Name, symbol and offering-document details stored in the mint itself with the
+// Token-2022 token-metadata extension
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SecurityMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub isin: String,
+    pub cusip: String,
+    pub document_hash: String,
+}
+
+impl SecurityMetadata {
+    pub fn additional_fields(&self) -> Vec<(String, String)> {
+        vec![
+            (ISIN_KEY.to_string(), self.isin.clone()),
+            (CUSIP_KEY.to_string(), self.cusip.clone()),
+            (DOCUMENT_HASH_KEY.to_string(), self.document_hash.clone())
+        ]
+    }
+
+    pub fn to_token_metadata(&self, update_authority: Pubkey, mint: Pubkey) -> Result<TokenMetadata> {
+        Ok(TokenMetadata {
+            update_authority: OptionalNonZeroPubkey::try_from(Some(update_authority))?,
+            mint,
+            name: self.name.clone(),
+            symbol: self.symbol.clone(),
+            uri: self.uri.clone(),
+            additional_metadata: self.additional_fields(),
+        })
+    }
+}
@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Subscription {
+    pub config: Pubkey,
+    pub investor: Pubkey,
+    pub recipient: Pubkey,
+    pub tokens: u64,
+    pub paid: u64,
+    pub settled: bool,
+    // Set instead of `settled` when the tokens would break the holding limits,
+    // the payment is then returned through `claim_refund`
+    pub refundable: bool,
+    pub bump: u8,
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_MILESTONES: usize = 10;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Milestone {
+    pub amount: u64,
+    pub achieved: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub enum VestingSchedule {
+    // Everything unlocks at `cliff_ts`
+    Cliff {
+        cliff_ts: i64,
+    },
+    // Unlocks linearly between `start_ts` and `end_ts`, nothing before `cliff_ts`
+    Linear {
+        start_ts: i64,
+        cliff_ts: i64,
+        end_ts: i64,
+    },
+    // Each milestone unlocks its amount once the issuer marks it achieved
+    Milestone {
+        #[max_len(MAX_MILESTONES)]
+        milestones: Vec<Milestone>,
+    },
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Vesting {
+    pub issuer: Pubkey,
+    pub beneficiary: Pubkey,
+    pub mint: Pubkey,
+    pub seed: u64,
+    pub total_amount: u64,
+    pub released: u64,
+    pub revoked: bool,
+    pub schedule: VestingSchedule,
+    pub bump: u8,
+}
+
+impl Vesting {
+    pub fn vested_amount(&self, now: i64) -> u64 {
+        // A revoked schedule keeps only what had vested at revocation
+        if self.revoked {
+            return self.total_amount;
+        }
+
+        match &self.schedule {
+            VestingSchedule::Cliff { cliff_ts } => {
+                if now >= *cliff_ts { self.total_amount } else { 0 }
+            }
+            VestingSchedule::Linear { start_ts, cliff_ts, end_ts } => {
+                if now < *cliff_ts || now <= *start_ts {
+                    0
+                } else if now >= *end_ts {
+                    self.total_amount
+                } else {
+                    let elapsed = (now - start_ts) as u128;
+                    let duration = (end_ts - start_ts) as u128;
+                    ((self.total_amount as u128) * elapsed / duration) as u64
+                }
+            }
+            VestingSchedule::Milestone { milestones } => {
+                milestones
+                    .iter()
+                    .filter(|m| m.achieved)
+                    .map(|m| m.amount)
+                    .sum()
+            }
+        }
+    }
+}
@@ -4,4 +4,25 @@ use anchor_lang::prelude::*;
 pub struct WhiteList {
     pub authority: Pubkey,
     pub white_list: Vec<Pubkey>,
-}
\ No newline at end of file
+}
+
+impl WhiteList {
+    // Account size needed to hold `len` entries
+    pub fn space(len: usize) -> usize {
+        8 + 32 + 4 + 32 * len
+    }
+
+    // Number of entries once `whitelist_batch` has removed `remove` and added `add`
+    pub fn len_after_batch(&self, add: &[Pubkey], remove: &[Pubkey]) -> usize {
+        let mut listed: Vec<&Pubkey> = self.white_list
+            .iter()
+            .filter(|listed| !remove.contains(listed))
+            .collect();
+        for account in add {
+            if !listed.contains(&account) {
+                listed.push(account);
+            }
+        }
+        listed.len()
+    }
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use anchor_lang::{ prelude::*, solana_program::entrypoint::ProgramResult, system_program, InstructionData };
+use common::*;
+use security_token_program::{
+    instruction,
+    Attestation,
+    AttestationError,
+    AuditAction,
+    AuditLog,
+    KycProviders,
+    Registry,
+    SecurityStatus,
+    ID as PROGRAM_ID,
+};
+
+struct AttestationAccounts {
+    providers: Vec<Pubkey>,
+    registry: TestAccount,
+    kyc_providers: TestAccount,
+    investor: TestAccount,
+    attestation: TestAccount,
+    audit_log: TestAccount,
+    system_program: TestAccount,
+}
+
+impl AttestationAccounts {
+    // An investor attested by the first of two trusted providers until `expires_at`
+    fn new(expires_at: i64) -> Self {
+        let authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let investor = Pubkey::new_unique();
+        let providers = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let (registry, bump) = Pubkey::find_program_address(&[b"registry", mint.as_ref()], &PROGRAM_ID);
+        let kyc_providers = pda(&[b"kyc_providers", mint.as_ref()]);
+        let (attestation, attestation_bump) = Pubkey::find_program_address(
+            &[b"attestation", mint.as_ref(), investor.as_ref()],
+            &PROGRAM_ID
+        );
+
+        AttestationAccounts {
+            registry: TestAccount::anchor(registry, &Registry {
+                mint,
+                admin: authority,
+                status: SecurityStatus::Active,
+                extra_account_meta_list: pda(&[b"extra-account-metas", mint.as_ref()]),
+                white_list: pda(&[b"white_list", mint.as_ref()]),
+                kyc_providers,
+                config: Pubkey::default(),
+                fee_config: Pubkey::default(),
+                bump,
+            }),
+            kyc_providers: TestAccount::anchor(kyc_providers, &KycProviders {
+                authority,
+                providers: providers.clone(),
+            }),
+            investor: TestAccount::wallet(investor),
+            attestation: TestAccount::anchor(attestation, &Attestation {
+                investor,
+                mint,
+                provider: providers[0],
+                level: 1,
+                expires_at,
+                bump: attestation_bump,
+            }).writable(),
+            audit_log: audit_log(pda(&[b"audit_log", mint.as_ref()]), &mint).writable(),
+            system_program: TestAccount::program(system_program::ID),
+            providers,
+        }
+    }
+
+    fn issue(&mut self, provider: Pubkey) -> ProgramResult {
+        let mut provider = TestAccount::wallet(provider).signer().writable();
+        let data = (instruction::IssueAttestation {
+            level: 2,
+            expires_at: NOW + 3600,
+            reason_hash: [3; 32],
+        }).data();
+        process(
+            &mut [
+                &mut provider,
+                &mut self.registry,
+                &mut self.kyc_providers,
+                &mut self.investor,
+                &mut self.attestation,
+                &mut self.audit_log,
+                &mut self.system_program,
+            ],
+            &data
+        )
+    }
+}
+
+#[test]
+fn provider_can_renew_its_attestation() {
+    let mut accounts = AttestationAccounts::new(NOW + 60);
+
+    accounts.issue(accounts.providers[0]).unwrap();
+
+    let attestation: Attestation = accounts.attestation.deserialize();
+    assert_eq!(attestation.level, 2);
+    assert_eq!(attestation.expires_at, NOW + 3600);
+
+    let audit_log: &AuditLog = bytemuck::from_bytes(&accounts.audit_log.data()[8..]);
+    assert_eq!(audit_log.total, 1);
+    assert_eq!(audit_log.entries[0].actor, accounts.providers[0]);
+    assert_eq!(audit_log.entries[0].action, AuditAction::AttestationIssue as u8);
+    assert_eq!(audit_log.entries[0].target, accounts.investor.key());
+}
+
+#[test]
+fn provider_cannot_replace_another_providers_attestation() {
+    let mut accounts = AttestationAccounts::new(NOW + 60);
+
+    let error = accounts.issue(accounts.providers[1]).unwrap_err();
+
+    assert_eq!(error, program_error(AttestationError::AttestedByOtherProvider));
+    let attestation: Attestation = accounts.attestation.deserialize();
+    assert_eq!(attestation.provider, accounts.providers[0]);
+}
+
+#[test]
+fn expired_attestation_can_be_replaced_by_another_provider() {
+    let mut accounts = AttestationAccounts::new(NOW);
+
+    accounts.issue(accounts.providers[1]).unwrap();
+
+    let attestation: Attestation = accounts.attestation.deserialize();
+    assert_eq!(attestation.provider, accounts.providers[1]);
+}
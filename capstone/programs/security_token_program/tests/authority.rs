@@ -0,0 +1,520 @@
+mod common;
+
+use anchor_lang::{ prelude::*, solana_program::entrypoint::ProgramResult, system_program, InstructionData };
+use common::*;
+use anchor_spl::token_2022::spl_token_2022;
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use security_token_program::{
+    check_hook_authority,
+    HookAccountAddress,
+    HookAccountConfig,
+    InitializeExtraAccountMetaList,
+    instruction,
+    AuditAction,
+    AuditLog,
+    Registry,
+    RegistryError,
+    SecurityStatus,
+    TransferError,
+    WhiteList,
+    WhiteListError,
+    WhitelistApplication,
+    ID as PROGRAM_ID,
+};
+
+struct WhiteListAccounts {
+    authority: Pubkey,
+    new_account: TestAccount,
+    registry: TestAccount,
+    white_list: TestAccount,
+    audit_log: TestAccount,
+    system_program: TestAccount,
+}
+
+impl WhiteListAccounts {
+    fn new() -> Self {
+        let authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let (registry, bump) = Pubkey::find_program_address(&[b"registry", mint.as_ref()], &PROGRAM_ID);
+        let white_list = pda(&[b"white_list", mint.as_ref()]);
+        let audit_log = pda(&[b"audit_log", mint.as_ref()]);
+
+        let mut white_list_account = TestAccount::anchor(white_list, &WhiteList {
+            authority,
+            white_list: Vec::new(),
+        }).writable();
+        // Funded up front so the realloc does not need a system program CPI
+        white_list_account.lamports = 1_000_000_000;
+
+        WhiteListAccounts {
+            authority,
+            new_account: empty(Pubkey::new_unique()),
+            registry: TestAccount::anchor(registry, &Registry {
+                mint,
+                admin: authority,
+                status: SecurityStatus::Draft,
+                extra_account_meta_list: pda(&[b"extra-account-metas", mint.as_ref()]),
+                white_list,
+                kyc_providers: pda(&[b"kyc_providers", mint.as_ref()]),
+                config: Pubkey::default(),
+                fee_config: Pubkey::default(),
+                bump,
+            }),
+            white_list: white_list_account,
+            audit_log: common::audit_log(audit_log, &mint).writable(),
+            system_program: TestAccount::program(system_program::ID),
+        }
+    }
+
+    fn whitelist_add(&mut self, signer: Pubkey) -> ProgramResult {
+        let mut signer = TestAccount::wallet(signer).signer().writable();
+        let data = (instruction::WhitelistAdd { reason_hash: [7; 32] }).data();
+        process(
+            &mut [
+                &mut self.new_account,
+                &mut self.registry,
+                &mut self.white_list,
+                &mut self.audit_log,
+                &mut signer,
+                &mut self.system_program,
+            ],
+            &data
+        )
+    }
+}
+
+impl WhiteListAccounts {
+    // Returns the authority account to check the rent it paid or received
+    fn whitelist_batch(&mut self, add: Vec<Pubkey>, remove: Vec<Pubkey>) -> TestAccount {
+        let mut authority = TestAccount::wallet(self.authority).signer().writable();
+        let data = (instruction::WhitelistBatch { add, remove, reason_hash: [0; 32] }).data();
+        process(
+            &mut [
+                &mut self.registry,
+                &mut self.white_list,
+                &mut self.audit_log,
+                &mut authority,
+                &mut self.system_program,
+            ],
+            &data
+        ).unwrap();
+        authority
+    }
+}
+
+#[test]
+fn authority_can_add_to_white_list() {
+    let mut accounts = WhiteListAccounts::new();
+    let before = accounts.white_list.data().len();
+
+    accounts.whitelist_add(accounts.authority).unwrap();
+
+    let white_list: WhiteList = accounts.white_list.deserialize();
+    assert_eq!(white_list.white_list, vec![accounts.new_account.key()]);
+    assert_eq!(accounts.white_list.data().len(), before + 32);
+
+    let audit_log: &AuditLog = bytemuck::from_bytes(&accounts.audit_log.data()[8..]);
+    assert_eq!(audit_log.total, 1);
+    let entry = &audit_log.entries[0];
+    assert_eq!(entry.slot, SLOT);
+    assert_eq!(entry.actor, accounts.authority);
+    assert_eq!(entry.action, AuditAction::WhitelistAdd as u8);
+    assert_eq!(entry.target, accounts.new_account.key());
+    assert_eq!(entry.reason_hash, [7; 32]);
+}
+
+#[test]
+fn batch_removal_shrinks_the_white_list_and_refunds_rent() {
+    let mut accounts = WhiteListAccounts::new();
+    let listed = [Pubkey::new_unique(), Pubkey::new_unique()];
+    accounts.whitelist_batch(listed.to_vec(), Vec::new());
+    let before = accounts.white_list.data().len();
+
+    // Re-adding a listed entry does not grow the account
+    let authority = accounts.whitelist_batch(vec![listed[1]], vec![listed[0]]);
+
+    let white_list: WhiteList = accounts.white_list.deserialize();
+    assert_eq!(white_list.white_list, vec![listed[1]]);
+    assert_eq!(accounts.white_list.data().len(), before - 32);
+    assert!(authority.lamports > 10_000_000_000, "excess rent is refunded to the authority");
+}
+
+#[test]
+fn only_authority_can_add_to_white_list() {
+    let mut accounts = WhiteListAccounts::new();
+
+    let result = accounts.whitelist_add(Pubkey::new_unique());
+
+    assert_eq!(result.unwrap_err(), program_error(WhiteListError::Unauthorized));
+    let white_list: WhiteList = accounts.white_list.deserialize();
+    assert!(white_list.white_list.is_empty());
+}
+
+#[test]
+fn adding_a_listed_account_is_a_no_op() {
+    let mut accounts = WhiteListAccounts::new();
+    accounts.whitelist_add(accounts.authority).unwrap();
+    let before = accounts.white_list.data().len();
+
+    accounts.whitelist_add(accounts.authority).unwrap();
+
+    let white_list: WhiteList = accounts.white_list.deserialize();
+    assert_eq!(white_list.white_list, vec![accounts.new_account.key()]);
+    assert_eq!(accounts.white_list.data().len(), before);
+    let audit_log: &AuditLog = bytemuck::from_bytes(&accounts.audit_log.data()[8..]);
+    assert_eq!(audit_log.total, 1);
+}
+
+#[test]
+fn hook_authority_is_checked() {
+    let authority = Pubkey::new_unique();
+    let mut mint = hooked_mint(Pubkey::new_unique(), &authority, None);
+    let mint_info = mint.account_info();
+
+    check_hook_authority(&mint_info, &authority).unwrap();
+    let error = check_hook_authority(&mint_info, &Pubkey::new_unique()).unwrap_err();
+    assert_eq!(ProgramError::from(error), program_error(TransferError::InvalidHookAuthority));
+}
+
+#[test]
+fn shrinking_the_meta_list_refunds_rent_to_the_authority() {
+    let authority = Pubkey::new_unique();
+    let mint_key = Pubkey::new_unique();
+    let configured = [HookAccountConfig {
+        address: HookAccountAddress::Fixed { address: Pubkey::new_unique() },
+        is_signer: false,
+        is_writable: false,
+    }];
+    let mut meta_list = extra_account_meta_list(
+        pda(&[b"extra-account-metas", mint_key.as_ref()]),
+        &configured
+    ).writable();
+    let before = meta_list.data().len();
+    let mut mint = hooked_mint(mint_key, &authority, None);
+    let mut signer = TestAccount::wallet(authority).signer().writable();
+    let mut audit_log = audit_log(pda(&[b"audit_log", mint_key.as_ref()]), &mint_key).writable();
+    let mut system_program = TestAccount::program(system_program::ID);
+
+    let data = (instruction::UpdateExtraAccountMetaList {
+        extra_account_metas: Vec::new(),
+        reason_hash: [5; 32],
+    }).data();
+    process(&mut [&mut signer, &mut meta_list, &mut mint, &mut audit_log, &mut system_program], &data).unwrap();
+
+    let after = meta_list.data().len();
+    assert!(after < before);
+    assert_eq!(meta_list.lamports, Rent::default().minimum_balance(after));
+    assert_eq!(signer.lamports, 10_000_000_000 + Rent::default().minimum_balance(before) - meta_list.lamports);
+
+    let audit_log: &AuditLog = bytemuck::from_bytes(&audit_log.data()[8..]);
+    assert_eq!(audit_log.total, 1);
+    let entry = &audit_log.entries[0];
+    assert_eq!(entry.actor, authority);
+    assert_eq!(entry.action, AuditAction::HookAccountsUpdate as u8);
+    assert_eq!(entry.target, meta_list.key());
+    assert_eq!(entry.reason_hash, [5; 32]);
+}
+
+#[test]
+fn updating_a_list_of_an_older_hook_layout_migrates_it() {
+    let authority = Pubkey::new_unique();
+    let mint_key = Pubkey::new_unique();
+    let key = pda(&[b"extra-account-metas", mint_key.as_ref()]);
+    let configured = [HookAccountConfig {
+        address: HookAccountAddress::Fixed { address: Pubkey::new_unique() },
+        is_signer: false,
+        is_writable: false,
+    }];
+    // Built-ins up to the bond holdings, before limits, modules, memos and config were read
+    let current = InitializeExtraAccountMetaList::extra_account_metas(&configured).unwrap();
+    let built_in = InitializeExtraAccountMetaList::extra_account_metas(&[]).unwrap().len();
+    let mut old = current[..built_in - 5].to_vec();
+    old.extend_from_slice(&current[built_in..]);
+    let mut data = vec![0; ExtraAccountMetaList::size_of(old.len()).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &old).unwrap();
+    let mut meta_list = TestAccount::new(key, PROGRAM_ID, &data).writable();
+    let mut mint = hooked_mint(mint_key, &authority, None);
+    let mut signer = TestAccount::wallet(authority).signer().writable();
+    let mut audit_log = audit_log(pda(&[b"audit_log", mint_key.as_ref()]), &mint_key).writable();
+    let mut system_program = TestAccount::program(system_program::ID);
+
+    let data = (instruction::UpdateExtraAccountMetaList {
+        extra_account_metas: configured.to_vec(),
+        reason_hash: [0; 32],
+    }).data();
+    process(&mut [&mut signer, &mut meta_list, &mut mint, &mut audit_log, &mut system_program], &data).unwrap();
+
+    assert_eq!(meta_list.data(), extra_account_meta_list(key, &configured).data());
+}
+
+#[test]
+fn only_registry_admin_can_change_status() {
+    let mut accounts = WhiteListAccounts::new();
+    let data = (instruction::SetRegistryStatus {
+        status: SecurityStatus::Active,
+        reason_hash: [0; 32],
+    }).data();
+
+    let mut intruder = TestAccount::wallet(Pubkey::new_unique()).signer();
+    accounts.registry.is_writable = true;
+    let result = process(&mut [&mut intruder, &mut accounts.registry, &mut accounts.audit_log], &data);
+    assert_eq!(result.unwrap_err(), program_error(RegistryError::Unauthorized));
+
+    let mut admin = TestAccount::wallet(accounts.authority).signer();
+    process(&mut [&mut admin, &mut accounts.registry, &mut accounts.audit_log], &data).unwrap();
+    let registry: Registry = accounts.registry.deserialize();
+    assert_eq!(registry.status, SecurityStatus::Active);
+}
+
+struct ApproveHolderAccounts {
+    admin: Pubkey,
+    registry: TestAccount,
+    mint: TestAccount,
+    token_account: TestAccount,
+    freeze_authority: TestAccount,
+    audit_log: TestAccount,
+    token_program: TestAccount,
+}
+
+impl ApproveHolderAccounts {
+    fn new() -> Self {
+        let admin = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let (registry, bump) = Pubkey::find_program_address(&[b"registry", mint.as_ref()], &PROGRAM_ID);
+        let token_account = token_account(Pubkey::new_unique(), &mint, &Pubkey::new_unique(), 0, false);
+
+        ApproveHolderAccounts {
+            admin,
+            registry: TestAccount::anchor(registry, &Registry {
+                mint,
+                admin,
+                status: SecurityStatus::Active,
+                extra_account_meta_list: pda(&[b"extra-account-metas", mint.as_ref()]),
+                white_list: pda(&[b"white_list", mint.as_ref()]),
+                kyc_providers: pda(&[b"kyc_providers", mint.as_ref()]),
+                config: Pubkey::default(),
+                fee_config: Pubkey::default(),
+                bump,
+            }),
+            mint: default_frozen_mint(mint, &admin),
+            token_account: frozen(&token_account).writable(),
+            freeze_authority: empty(pda(&[b"freeze_authority", mint.as_ref()])),
+            audit_log: common::audit_log(pda(&[b"audit_log", mint.as_ref()]), &mint).writable(),
+            token_program: TestAccount::program(spl_token_2022::ID),
+        }
+    }
+
+    fn approve_holder(&mut self, signer: Pubkey) -> ProgramResult {
+        let mut signer = TestAccount::wallet(signer).signer();
+        let data = (instruction::ApproveHolder { reason_hash: [3; 32] }).data();
+        process(
+            &mut [
+                &mut signer,
+                &mut self.registry,
+                &mut self.mint,
+                &mut self.token_account,
+                &mut self.freeze_authority,
+                &mut self.audit_log,
+                &mut self.token_program,
+            ],
+            &data
+        )
+    }
+
+    fn revoke_holder(&mut self, signer: Pubkey) -> ProgramResult {
+        let mut signer = TestAccount::wallet(signer).signer();
+        let data = (instruction::RevokeHolder { reason_hash: [4; 32] }).data();
+        process(
+            &mut [
+                &mut signer,
+                &mut self.registry,
+                &mut self.mint,
+                &mut self.token_account,
+                &mut self.freeze_authority,
+                &mut self.audit_log,
+                &mut self.token_program,
+            ],
+            &data
+        )
+    }
+
+    fn thaw(&mut self) {
+        let mint = self.mint.key();
+        self.token_account = token_account(self.token_account.key(), &mint, &Pubkey::new_unique(), 0, false).writable();
+    }
+}
+
+#[test]
+fn approve_holder_thaws_with_the_freeze_authority() {
+    let mut accounts = ApproveHolderAccounts::new();
+
+    accounts.approve_holder(accounts.admin).unwrap();
+
+    let thaw = invoked().pop().expect("thaw instruction");
+    assert_eq!(thaw.program_id, spl_token_2022::ID);
+    assert_eq!(thaw.accounts[0].pubkey, accounts.token_account.key());
+    assert_eq!(thaw.accounts[2].pubkey, accounts.freeze_authority.key());
+    assert!(thaw.accounts[2].is_signer);
+
+    let audit_log: &AuditLog = bytemuck::from_bytes(&accounts.audit_log.data()[8..]);
+    assert_eq!(audit_log.entries[0].action, AuditAction::HolderApproved as u8);
+    assert_eq!(audit_log.entries[0].target, accounts.token_account.key());
+}
+
+#[test]
+fn only_registry_admin_can_approve_holders() {
+    let mut accounts = ApproveHolderAccounts::new();
+
+    let result = accounts.approve_holder(Pubkey::new_unique());
+    assert_eq!(result.unwrap_err(), program_error(RegistryError::Unauthorized));
+}
+
+#[test]
+fn approve_holder_requires_a_frozen_account() {
+    let mut accounts = ApproveHolderAccounts::new();
+    accounts.thaw();
+
+    let result = accounts.approve_holder(accounts.admin);
+    assert_eq!(result.unwrap_err(), program_error(RegistryError::AccountNotFrozen));
+}
+
+#[test]
+fn revoke_holder_freezes_with_the_freeze_authority() {
+    let mut accounts = ApproveHolderAccounts::new();
+    accounts.thaw();
+
+    accounts.revoke_holder(accounts.admin).unwrap();
+
+    let freeze = invoked().pop().expect("freeze instruction");
+    assert_eq!(freeze.program_id, spl_token_2022::ID);
+    assert_eq!(freeze.accounts[0].pubkey, accounts.token_account.key());
+    assert_eq!(freeze.accounts[2].pubkey, accounts.freeze_authority.key());
+    assert!(freeze.accounts[2].is_signer);
+
+    let audit_log: &AuditLog = bytemuck::from_bytes(&accounts.audit_log.data()[8..]);
+    assert_eq!(audit_log.entries[0].action, AuditAction::HolderRevoked as u8);
+    assert_eq!(audit_log.entries[0].target, accounts.token_account.key());
+    assert_eq!(audit_log.entries[0].reason_hash, [4; 32]);
+}
+
+#[test]
+fn only_registry_admin_can_revoke_holders() {
+    let mut accounts = ApproveHolderAccounts::new();
+    accounts.thaw();
+
+    let result = accounts.revoke_holder(Pubkey::new_unique());
+    assert_eq!(result.unwrap_err(), program_error(RegistryError::Unauthorized));
+}
+
+#[test]
+fn revoke_holder_requires_a_thawed_account() {
+    let mut accounts = ApproveHolderAccounts::new();
+
+    let result = accounts.revoke_holder(accounts.admin);
+    assert_eq!(result.unwrap_err(), program_error(RegistryError::AccountAlreadyFrozen));
+}
+
+impl WhiteListAccounts {
+    fn resolve_application(&mut self, signer: Pubkey, approve: bool) -> (ProgramResult, TestAccount) {
+        let registry: Registry = self.registry.deserialize();
+        let applicant = Pubkey::new_unique();
+        let (application, bump) = Pubkey::find_program_address(
+            &[b"application", registry.mint.as_ref(), applicant.as_ref()],
+            &PROGRAM_ID
+        );
+        let mut application = TestAccount::anchor(application, &WhitelistApplication {
+            mint: registry.mint,
+            applicant,
+            token_account: self.new_account.key(),
+            document_hash: [9; 32],
+            created_at: NOW,
+            bump,
+        }).writable();
+        let mut applicant = TestAccount::wallet(applicant).writable();
+        let mut signer = TestAccount::wallet(signer).signer().writable();
+
+        let result = if approve {
+            let data = (instruction::ApproveApplication { reason_hash: [1; 32] }).data();
+            process(
+                &mut [
+                    &mut signer,
+                    &mut self.registry,
+                    &mut self.white_list,
+                    &mut application,
+                    &mut applicant,
+                    &mut self.audit_log,
+                    &mut self.system_program,
+                ],
+                &data
+            )
+        } else {
+            let data = (instruction::RejectApplication { reason_hash: [1; 32] }).data();
+            process(
+                &mut [
+                    &mut signer,
+                    &mut self.registry,
+                    &mut self.white_list,
+                    &mut application,
+                    &mut applicant,
+                    &mut self.audit_log,
+                ],
+                &data
+            )
+        };
+        assert_eq!(result.is_ok(), application.lamports == 0, "application is closed on resolution");
+        (result, applicant)
+    }
+}
+
+#[test]
+fn approved_application_is_whitelisted_and_refunded() {
+    let mut accounts = WhiteListAccounts::new();
+
+    let (result, applicant) = accounts.resolve_application(accounts.authority, true);
+    result.unwrap();
+
+    let white_list: WhiteList = accounts.white_list.deserialize();
+    assert_eq!(white_list.white_list, vec![accounts.new_account.key()]);
+    assert!(applicant.lamports > 10_000_000_000, "rent returned to the applicant");
+    let audit_log: &AuditLog = bytemuck::from_bytes(&accounts.audit_log.data()[8..]);
+    assert_eq!(audit_log.entries[0].action, AuditAction::WhitelistAdd as u8);
+}
+
+#[test]
+fn approving_a_listed_account_does_not_grow_the_white_list() {
+    let mut accounts = WhiteListAccounts::new();
+    accounts.resolve_application(accounts.authority, true).0.unwrap();
+    let before = accounts.white_list.data().len();
+
+    let (result, _) = accounts.resolve_application(accounts.authority, true);
+    result.unwrap();
+
+    let white_list: WhiteList = accounts.white_list.deserialize();
+    assert_eq!(white_list.white_list, vec![accounts.new_account.key()]);
+    assert_eq!(accounts.white_list.data().len(), before);
+}
+
+#[test]
+fn rejected_application_is_refunded_without_whitelisting() {
+    let mut accounts = WhiteListAccounts::new();
+
+    let (result, applicant) = accounts.resolve_application(accounts.authority, false);
+    result.unwrap();
+
+    let white_list: WhiteList = accounts.white_list.deserialize();
+    assert!(white_list.white_list.is_empty());
+    assert!(applicant.lamports > 10_000_000_000, "rent returned to the applicant");
+    let audit_log: &AuditLog = bytemuck::from_bytes(&accounts.audit_log.data()[8..]);
+    assert_eq!(audit_log.entries[0].action, AuditAction::ApplicationRejected as u8);
+}
+
+#[test]
+fn only_white_list_authority_resolves_applications() {
+    let mut accounts = WhiteListAccounts::new();
+
+    for approve in [true, false] {
+        let (result, _) = accounts.resolve_application(Pubkey::new_unique(), approve);
+        assert_eq!(result.unwrap_err(), program_error(WhiteListError::Unauthorized));
+    }
+}
@@ -0,0 +1,407 @@
+// Unit harness, not an integration test: instructions are run through the
+// program's `entry` with accounts laid out in memory the way the runtime
+// serializes them, and with the clock, rent and stack height syscalls stubbed,
+// so the suite runs offline without a validator. There is no runtime behind it:
+// CPIs are recorded rather than executed, Token-2022 never runs its own checks
+// or calls the hook, and account ownership, rent exemption and signer
+// privileges are whatever the test says they are. Instructions that rely on
+// CPI effects (account creation, token transfers) are out of reach; accounts
+// are built directly in their post-CPI state instead. End-to-end behaviour is
+// covered by the TypeScript suite in `tests/test.ts` against a local validator.
+//
+// TODO: the runtime-backed Rust suite (hooked mint, meta list, transfers going
+// through Token-2022 into the hook) is still missing. `solana-program-test`
+// 1.18.0 pins the yanked `solana_rbpf = "=0.8.0"` and `solana-program =
+// "=1.18.0"`, below the 1.18.16 this program needs, so it does not resolve
+// against this tree. It needs either a LiteSVM dev-dependency or a committed
+// `Cargo.lock` (currently ignored) pinning a `solana-program-test` of the same
+// 1.18 release as `solana-program`.
+#![allow(dead_code)]
+
+use std::{ cell::RefCell, collections::HashMap, sync::Once };
+
+use anchor_lang::{
+    prelude::*,
+    system_program,
+    solana_program::{
+        entrypoint::{ ProgramResult, MAX_PERMITTED_DATA_INCREASE },
+        instruction::Instruction,
+        program_error::ProgramError,
+        program_option::COption,
+        program_stubs::{ set_syscall_stubs, SyscallStubs },
+        sysvar::{
+            self,
+            instructions::{
+                construct_instructions_data,
+                store_current_index,
+                BorrowedAccountMeta,
+                BorrowedInstruction,
+            },
+        },
+    },
+    AccountSerialize,
+    Discriminator,
+};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        default_account_state::DefaultAccountState,
+        permanent_delegate::PermanentDelegate,
+        transfer_hook::{ TransferHook as TransferHookExtension, TransferHookAccount },
+        BaseStateWithExtensionsMut,
+        ExtensionType,
+        StateWithExtensionsMut,
+    },
+    state::{ Account as TokenAccountState, AccountState, Mint as MintState },
+};
+use anchor_spl::token_interface::spl_pod::optional_keys::OptionalNonZeroPubkey;
+use security_token_program::{ AuditLog, HookAccountConfig, InitializeExtraAccountMetaList, ID as PROGRAM_ID };
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+pub const NOW: i64 = 1_700_000_000;
+pub const SLOT: u64 = 100;
+
+thread_local! {
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(Vec::new()) };
+    static CPI_RETURN_DATA: RefCell<HashMap<Pubkey, Vec<u8>>> = RefCell::new(HashMap::new());
+    static RETURN_DATA: RefCell<Option<(Pubkey, Vec<u8>)>> = const { RefCell::new(None) };
+    static STACK_HEIGHT: RefCell<u64> = const { RefCell::new(1) };
+}
+
+// Instructions run top level unless a test nests them, as if invoked by CPI
+pub fn set_stack_height(height: u64) {
+    STACK_HEIGHT.with(|stack_height| *stack_height.borrow_mut() = height);
+}
+
+// Return data set by `program` whenever it is invoked
+pub fn set_cpi_return_data(program: Pubkey, data: Vec<u8>) {
+    CPI_RETURN_DATA.with(|responses| responses.borrow_mut().insert(program, data));
+}
+
+// Return data left in the transaction before the program runs
+pub fn set_return_data(program: Pubkey, data: Vec<u8>) {
+    RETURN_DATA.with(|return_data| *return_data.borrow_mut() = Some((program, data)));
+}
+
+// Instructions invoked by the program on this thread, in order
+pub fn invoked() -> Vec<Instruction> {
+    INVOKED.with(|invoked| invoked.borrow().clone())
+}
+
+struct TestStubs;
+
+impl SyscallStubs for TestStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        _account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]]
+    ) -> ProgramResult {
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        if let Some(data) = CPI_RETURN_DATA.with(|responses| responses.borrow().get(&instruction.program_id).cloned()) {
+            RETURN_DATA.with(|return_data| *return_data.borrow_mut() = Some((instruction.program_id, data)));
+        }
+        Ok(())
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        RETURN_DATA.with(|return_data| return_data.borrow().clone())
+    }
+
+    // Like the runtime, empty return data reads back as none
+    fn sol_set_return_data(&self, data: &[u8]) {
+        let data = (!data.is_empty()).then(|| (PROGRAM_ID, data.to_vec()));
+        RETURN_DATA.with(|return_data| *return_data.borrow_mut() = data);
+    }
+
+    fn sol_get_stack_height(&self) -> u64 {
+        STACK_HEIGHT.with(|stack_height| *stack_height.borrow())
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            slot: SLOT,
+            unix_timestamp: NOW,
+            ..Clock::default()
+        };
+        unsafe {
+            *(var_addr as *mut Clock) = clock;
+        }
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe {
+            *(var_addr as *mut Rent) = Rent::default();
+        }
+        0
+    }
+}
+
+pub fn setup() {
+    static STUBS: Once = Once::new();
+    STUBS.call_once(|| {
+        set_syscall_stubs(Box::new(TestStubs));
+    });
+}
+
+// [padding: 4][original data len: u32][key: 32][data len: u64][data + realloc space]
+// `AccountInfo::realloc` reads and writes the lengths stored before the key and data.
+const KEY_OFFSET: usize = 8;
+const DATA_LEN_OFFSET: usize = 40;
+const DATA_OFFSET: usize = 48;
+
+pub struct TestAccount {
+    buffer: Vec<u64>,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub is_signer: bool,
+    pub is_writable: bool,
+    pub executable: bool,
+}
+
+impl TestAccount {
+    pub fn new(key: Pubkey, owner: Pubkey, data: &[u8]) -> Self {
+        let size = DATA_OFFSET + data.len() + MAX_PERMITTED_DATA_INCREASE;
+        let mut buffer = vec![0u64; size.div_ceil(8)];
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut buffer);
+        bytes[4..KEY_OFFSET].copy_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes[KEY_OFFSET..DATA_LEN_OFFSET].copy_from_slice(key.as_ref());
+        bytes[DATA_LEN_OFFSET..DATA_OFFSET].copy_from_slice(&(data.len() as u64).to_le_bytes());
+        bytes[DATA_OFFSET..DATA_OFFSET + data.len()].copy_from_slice(data);
+
+        TestAccount {
+            buffer,
+            owner,
+            lamports: Rent::default().minimum_balance(data.len()),
+            is_signer: false,
+            is_writable: false,
+            executable: false,
+        }
+    }
+
+    pub fn wallet(key: Pubkey) -> Self {
+        let mut account = TestAccount::new(key, system_program::ID, &[]);
+        account.lamports = 10_000_000_000;
+        account
+    }
+
+    pub fn program(key: Pubkey) -> Self {
+        let mut account = TestAccount::new(key, Pubkey::default(), &[]);
+        account.executable = true;
+        account
+    }
+
+    pub fn anchor<T: AccountSerialize>(key: Pubkey, account: &T) -> Self {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        TestAccount::new(key, PROGRAM_ID, &data)
+    }
+
+    pub fn signer(mut self) -> Self {
+        self.is_signer = true;
+        self
+    }
+
+    pub fn writable(mut self) -> Self {
+        self.is_writable = true;
+        self
+    }
+
+    fn bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(&self.buffer)
+    }
+
+    pub fn key(&self) -> Pubkey {
+        Pubkey::try_from(&self.bytes()[KEY_OFFSET..DATA_LEN_OFFSET]).unwrap()
+    }
+
+    pub fn data(&self) -> &[u8] {
+        let len = u64::from_le_bytes(self.bytes()[DATA_LEN_OFFSET..DATA_OFFSET].try_into().unwrap());
+        &self.bytes()[DATA_OFFSET..DATA_OFFSET + len as usize]
+    }
+
+    pub fn deserialize<T: AccountDeserialize>(&self) -> T {
+        T::try_deserialize(&mut self.data()).unwrap()
+    }
+
+    pub fn account_info(&mut self) -> AccountInfo<'_> {
+        let ptr = self.buffer.as_mut_ptr() as *mut u8;
+        // Key and data are disjoint regions of the buffer
+        let (key, data) = unsafe {
+            let len = *(ptr.add(DATA_LEN_OFFSET) as *const u64) as usize;
+            (
+                &*(ptr.add(KEY_OFFSET) as *const Pubkey),
+                std::slice::from_raw_parts_mut(ptr.add(DATA_OFFSET), len),
+            )
+        };
+        AccountInfo::new(
+            key,
+            self.is_signer,
+            self.is_writable,
+            &mut self.lamports,
+            data,
+            &self.owner,
+            self.executable,
+            0
+        )
+    }
+}
+
+// Runs one instruction of the program against `accounts`, in order
+pub fn process(accounts: &mut [&mut TestAccount], data: &[u8]) -> ProgramResult {
+    setup();
+    let infos: Vec<AccountInfo> = accounts
+        .iter_mut()
+        .map(|account| account.account_info())
+        .collect();
+    // `entry` wants the slice to live as long as the accounts it borrows
+    let infos: &[AccountInfo] = infos.leak();
+    security_token_program::entry(&PROGRAM_ID, infos, data)
+}
+
+pub fn program_error<E: Into<anchor_lang::error::Error>>(error: E) -> ProgramError {
+    error.into().into()
+}
+
+pub fn assert_rejected(result: Result<()>, expected: impl Into<anchor_lang::error::Error>) {
+    let error: ProgramError = result.unwrap_err().into();
+    assert_eq!(error, program_error(expected));
+}
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &PROGRAM_ID).0
+}
+
+// Token-2022 mint with this program as its transfer hook
+pub fn hooked_mint(key: Pubkey, authority: &Pubkey, permanent_delegate: Option<Pubkey>) -> TestAccount {
+    mint(key, authority, permanent_delegate, false)
+}
+
+// Hooked mint whose new token accounts start frozen, thawed by `approve_holder`
+pub fn default_frozen_mint(key: Pubkey, authority: &Pubkey) -> TestAccount {
+    mint(key, authority, None, true)
+}
+
+fn mint(key: Pubkey, authority: &Pubkey, permanent_delegate: Option<Pubkey>, default_frozen: bool) -> TestAccount {
+    let mut extensions = vec![ExtensionType::TransferHook];
+    if permanent_delegate.is_some() {
+        extensions.push(ExtensionType::PermanentDelegate);
+    }
+    if default_frozen {
+        extensions.push(ExtensionType::DefaultAccountState);
+    }
+    let len = ExtensionType::try_calculate_account_len::<MintState>(&extensions).unwrap();
+    let mut data = vec![0; len];
+    let mut state = StateWithExtensionsMut::<MintState>::unpack_uninitialized(&mut data).unwrap();
+    let hook = state.init_extension::<TransferHookExtension>(true).unwrap();
+    hook.authority = OptionalNonZeroPubkey::try_from(Some(*authority)).unwrap();
+    hook.program_id = OptionalNonZeroPubkey::try_from(Some(PROGRAM_ID)).unwrap();
+    if permanent_delegate.is_some() {
+        let extension = state.init_extension::<PermanentDelegate>(true).unwrap();
+        extension.delegate = OptionalNonZeroPubkey::try_from(permanent_delegate).unwrap();
+    }
+    let mut freeze_authority = COption::None;
+    if default_frozen {
+        state.init_extension::<DefaultAccountState>(true).unwrap().state = AccountState::Frozen as u8;
+        freeze_authority = COption::Some(pda(&[b"freeze_authority", key.as_ref()]));
+    }
+    state.base = MintState {
+        mint_authority: COption::Some(*authority),
+        supply: 1_000_000,
+        decimals: 0,
+        is_initialized: true,
+        freeze_authority,
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+
+    TestAccount::new(key, spl_token_2022::ID, &data)
+}
+
+// Token-2022 account of a hooked mint. `transferring` is the flag Token-2022
+// raises on the source account for the duration of the hook call.
+pub fn token_account(
+    key: Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+    transferring: bool
+) -> TestAccount {
+    let len = ExtensionType::try_calculate_account_len::<TokenAccountState>(
+        &[ExtensionType::TransferHookAccount]
+    ).unwrap();
+    let mut data = vec![0; len];
+    let mut state = StateWithExtensionsMut::<TokenAccountState>::unpack_uninitialized(&mut data).unwrap();
+    let extension = state.init_extension::<TransferHookAccount>(true).unwrap();
+    extension.transferring = transferring.into();
+    state.base = TokenAccountState {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: AccountState::Initialized,
+        ..TokenAccountState::default()
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+
+    TestAccount::new(key, spl_token_2022::ID, &data)
+}
+
+pub fn with_delegate(account: &TestAccount, delegate: &Pubkey, amount: u64) -> TestAccount {
+    let mut data = account.data().to_vec();
+    let mut state = StateWithExtensionsMut::<TokenAccountState>::unpack(&mut data).unwrap();
+    state.base.delegate = COption::Some(*delegate);
+    state.base.delegated_amount = amount;
+    state.pack_base();
+    TestAccount::new(account.key(), account.owner, &data)
+}
+
+pub fn frozen(account: &TestAccount) -> TestAccount {
+    let mut data = account.data().to_vec();
+    let mut state = StateWithExtensionsMut::<TokenAccountState>::unpack(&mut data).unwrap();
+    state.base.state = AccountState::Frozen;
+    state.pack_base();
+    TestAccount::new(account.key(), account.owner, &data)
+}
+
+pub fn extra_account_meta_list(key: Pubkey, configured: &[HookAccountConfig]) -> TestAccount {
+    let metas = InitializeExtraAccountMetaList::extra_account_metas(configured).unwrap();
+    let mut data = vec![0; ExtraAccountMetaList::size_of(metas.len()).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &metas).unwrap();
+    TestAccount::new(key, PROGRAM_ID, &data)
+}
+
+pub fn audit_log(key: Pubkey, mint: &Pubkey) -> TestAccount {
+    let mut data = vec![0; 8 + std::mem::size_of::<AuditLog>()];
+    data[..8].copy_from_slice(&AuditLog::DISCRIMINATOR);
+    data[8..40].copy_from_slice(mint.as_ref());
+    TestAccount::new(key, PROGRAM_ID, &data)
+}
+
+// Instructions sysvar of a transaction made of `instructions`, executing the one at `current_index`
+pub fn instructions_sysvar(instructions: &[Instruction], current_index: u16) -> TestAccount {
+    let borrowed: Vec<BorrowedInstruction> = instructions
+        .iter()
+        .map(|ix| BorrowedInstruction {
+            program_id: &ix.program_id,
+            accounts: ix.accounts
+                .iter()
+                .map(|meta| BorrowedAccountMeta {
+                    pubkey: &meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: &ix.data,
+        })
+        .collect();
+    let mut data = construct_instructions_data(&borrowed);
+    store_current_index(&mut data, current_index);
+    TestAccount::new(sysvar::instructions::ID, sysvar::ID, &data)
+}
+
+pub fn empty(key: Pubkey) -> TestAccount {
+    TestAccount::new(key, system_program::ID, &[])
+}
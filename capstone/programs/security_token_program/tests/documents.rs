@@ -0,0 +1,264 @@
+mod common;
+
+use std::collections::HashMap;
+
+use anchor_lang::{ prelude::*, system_program, Discriminator, InstructionData };
+use common::*;
+use security_token_program::{
+    instruction,
+    AuditAction,
+    AuditLog,
+    Document,
+    DocumentError,
+    DocumentHead,
+    DocumentRegistry,
+    Registry,
+    RegistryError,
+    SecurityStatus,
+    ID as PROGRAM_ID,
+};
+
+struct DocumentAccounts {
+    admin: Pubkey,
+    mint: Pubkey,
+    registry: TestAccount,
+    documents: TestAccount,
+    heads: HashMap<String, TestAccount>,
+    versions: Vec<TestAccount>,
+    audit_log: TestAccount,
+    system_program: TestAccount,
+}
+
+impl DocumentAccounts {
+    fn new() -> Self {
+        let admin = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let (registry, bump) = Pubkey::find_program_address(&[b"registry", mint.as_ref()], &PROGRAM_ID);
+        let (documents, documents_bump) = Pubkey::find_program_address(
+            &[b"documents", mint.as_ref()],
+            &PROGRAM_ID
+        );
+
+        DocumentAccounts {
+            admin,
+            mint,
+            registry: TestAccount::anchor(registry, &Registry {
+                mint,
+                admin,
+                status: SecurityStatus::Active,
+                extra_account_meta_list: pda(&[b"extra-account-metas", mint.as_ref()]),
+                white_list: pda(&[b"white_list", mint.as_ref()]),
+                kyc_providers: pda(&[b"kyc_providers", mint.as_ref()]),
+                config: Pubkey::default(),
+                fee_config: Pubkey::default(),
+                bump,
+            }),
+            documents: TestAccount::anchor(documents, &DocumentRegistry {
+                mint,
+                bump: documents_bump,
+                versions: 0,
+            }).writable(),
+            heads: HashMap::new(),
+            versions: Vec::new(),
+            audit_log: audit_log(pda(&[b"audit_log", mint.as_ref()]), &mint).writable(),
+            system_program: TestAccount::program(system_program::ID),
+        }
+    }
+
+    // The head of `name`, as left by earlier calls. A new one stands in for the
+    // account `init_if_needed` would create, at its full size.
+    fn head(&mut self, name: &str) -> TestAccount {
+        let key = pda(&[b"document_head", self.mint.as_ref(), DocumentHead::seed(name).as_ref()]);
+        self.heads.remove(name).unwrap_or_else(|| {
+            let mut data = vec![0; 8 + DocumentHead::INIT_SPACE];
+            data[..8].copy_from_slice(&DocumentHead::DISCRIMINATOR);
+            TestAccount::new(key, PROGRAM_ID, &data).writable()
+        })
+    }
+
+    fn current(&self, name: &str) -> Option<usize> {
+        self.heads.get(name).and_then(|head| head.deserialize::<DocumentHead>().current).map(|index| index as usize)
+    }
+
+    fn set_document(&mut self, signer: Pubkey, name: &str, uri: &str, content_hash: [u8; 32]) -> Result<()> {
+        let previous = self.current(name);
+        self.set_document_after(signer, name, uri, content_hash, previous)
+    }
+
+    // Sets a version passing `previous` as the version in force
+    fn set_document_after(
+        &mut self,
+        signer: Pubkey,
+        name: &str,
+        uri: &str,
+        content_hash: [u8; 32],
+        previous: Option<usize>
+    ) -> Result<()> {
+        let mut signer = TestAccount::wallet(signer).signer().writable();
+        let mut head = self.head(name);
+        let index = self.versions.len() as u32;
+        // Allocated where `init` would create it
+        let mut document = TestAccount::new(
+            pda(&[b"document", self.mint.as_ref(), index.to_le_bytes().as_ref()]),
+            PROGRAM_ID,
+            &vec![0; Document::space(name, uri)]
+        ).writable();
+        let mut none = TestAccount::program(PROGRAM_ID);
+        let data = (instruction::SetDocument {
+            name: name.to_string(),
+            uri: uri.to_string(),
+            content_hash,
+            reason_hash: [0; 32],
+        }).data();
+
+        let previous = match previous {
+            Some(index) => &mut self.versions[index],
+            None => &mut none,
+        };
+        let result = process(
+            &mut [
+                &mut signer,
+                &mut self.registry,
+                &mut self.documents,
+                &mut head,
+                previous,
+                &mut document,
+                &mut self.audit_log,
+                &mut self.system_program,
+            ],
+            &data
+        );
+        self.heads.insert(name.to_string(), head);
+        if result.is_ok() {
+            self.versions.push(document);
+        }
+        result.map_err(Into::into)
+    }
+
+    fn remove_document(&mut self, name: &str, current: usize) -> Result<()> {
+        let mut signer = TestAccount::wallet(self.admin).signer();
+        let mut head = self.head(name);
+        let data = (instruction::RemoveDocument { name: name.to_string(), reason_hash: [0; 32] }).data();
+        let result = process(
+            &mut [&mut signer, &mut self.registry, &mut head, &mut self.versions[current], &mut self.audit_log],
+            &data
+        );
+        self.heads.insert(name.to_string(), head);
+        result.map_err(Into::into)
+    }
+
+    fn history(&self) -> Vec<Document> {
+        self.versions
+            .iter()
+            .map(|version| version.deserialize())
+            .collect()
+    }
+}
+
+#[test]
+fn new_version_supersedes_the_previous_one() {
+    let mut accounts = DocumentAccounts::new();
+
+    accounts.set_document(accounts.admin, "prospectus", "ipfs://v1", [1; 32]).unwrap();
+    accounts.set_document(accounts.admin, "prospectus", "ipfs://v2", [2; 32]).unwrap();
+
+    let history = accounts.history();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].until_slot, Some(SLOT));
+    assert_eq!(history[1].index, 1);
+    assert_eq!(history[1].uri, "ipfs://v2");
+    assert_eq!(history[1].content_hash, [2; 32]);
+    assert_eq!((history[1].from_slot, history[1].until_slot), (SLOT, None));
+    assert_eq!(history[1].timestamp, NOW);
+    assert_eq!(accounts.current("prospectus"), Some(1));
+    assert_eq!(accounts.documents.deserialize::<DocumentRegistry>().versions, 2);
+}
+
+#[test]
+fn versions_of_other_names_are_left_in_force() {
+    let mut accounts = DocumentAccounts::new();
+    accounts.set_document(accounts.admin, "prospectus", "ipfs://v1", [1; 32]).unwrap();
+
+    accounts.set_document(accounts.admin, "notice", "ipfs://notice", [2; 32]).unwrap();
+
+    let history = accounts.history();
+    assert_eq!((history[0].until_slot, history[1].until_slot), (None, None));
+    assert_eq!(accounts.current("notice"), Some(1));
+}
+
+#[test]
+fn new_version_must_end_the_one_in_force() {
+    let mut accounts = DocumentAccounts::new();
+    accounts.set_document(accounts.admin, "prospectus", "ipfs://v1", [1; 32]).unwrap();
+    accounts.set_document(accounts.admin, "notice", "ipfs://notice", [2; 32]).unwrap();
+
+    let admin = accounts.admin;
+    let result = accounts.set_document_after(admin, "prospectus", "ipfs://v2", [3; 32], None);
+    assert_rejected(result, DocumentError::NotCurrentVersion);
+    let result = accounts.set_document_after(admin, "prospectus", "ipfs://v2", [3; 32], Some(1));
+    assert_rejected(result, DocumentError::NotCurrentVersion);
+}
+
+#[test]
+fn removed_document_stays_in_the_history() {
+    let mut accounts = DocumentAccounts::new();
+    accounts.set_document(accounts.admin, "notice", "https://issuer/notice", [3; 32]).unwrap();
+
+    accounts.remove_document("notice", 0).unwrap();
+
+    let history = accounts.history();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].until_slot, Some(SLOT));
+    assert_eq!(accounts.current("notice"), None);
+    let audit_log: &AuditLog = bytemuck::from_bytes(&accounts.audit_log.data()[8..]);
+    assert_eq!(audit_log.total, 2);
+    assert_eq!(audit_log.entries[0].action, AuditAction::DocumentSet as u8);
+    assert_eq!(audit_log.entries[1].action, AuditAction::DocumentRemove as u8);
+    assert_eq!(audit_log.entries[1].target, accounts.versions[0].key());
+    assert_rejected(accounts.remove_document("notice", 0), DocumentError::UnknownDocument);
+
+    // Set again, the name starts a new version without one to end
+    accounts.set_document(accounts.admin, "notice", "https://issuer/notice-2", [4; 32]).unwrap();
+    assert_eq!(accounts.current("notice"), Some(1));
+}
+
+#[test]
+fn versions_in_force_at_a_slot() {
+    let version = |from_slot, until_slot| Document {
+        mint: Pubkey::new_unique(),
+        index: 0,
+        name: "prospectus".to_string(),
+        uri: String::new(),
+        content_hash: [0; 32],
+        timestamp: 0,
+        from_slot,
+        until_slot,
+        bump: 0,
+    };
+
+    assert!(!version(10, Some(20)).in_force_at(5));
+    assert!(version(10, Some(20)).in_force_at(19));
+    assert!(!version(10, Some(20)).in_force_at(20));
+    assert!(version(20, None).in_force_at(u64::MAX));
+}
+
+#[test]
+fn only_registry_admin_can_set_documents() {
+    let mut accounts = DocumentAccounts::new();
+
+    let result = accounts.set_document(Pubkey::new_unique(), "prospectus", "ipfs://v1", [1; 32]);
+    assert_rejected(result, RegistryError::Unauthorized);
+    assert_rejected(
+        accounts.set_document(accounts.admin, "", "ipfs://v1", [1; 32]),
+        DocumentError::EmptyName
+    );
+}
+
+#[test]
+fn overlong_names_are_rejected() {
+    let mut accounts = DocumentAccounts::new();
+    let name = "n".repeat(33);
+
+    let result = accounts.set_document(accounts.admin, &name, "ipfs://v1", [1; 32]);
+    assert_rejected(result, DocumentError::NameTooLong);
+}
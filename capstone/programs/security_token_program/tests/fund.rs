@@ -0,0 +1,498 @@
+mod common;
+
+use anchor_lang::{ prelude::*, solana_program::entrypoint::ProgramResult, InstructionData };
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_2022::spl_token_2022::{ self, instruction::TokenInstruction },
+};
+use common::*;
+use security_token_program::{
+    instruction,
+    payment_for,
+    redemption_fill,
+    shares_for,
+    Bond,
+    BondHolding,
+    Fund,
+    FundError,
+    FundRequest,
+    FundRequestKind,
+    HoldingLimits,
+    NavRecord,
+    COUPON_INDEX_PRECISION,
+    NAV_PRECISION,
+    ID as PROGRAM_ID,
+};
+
+const NAV: u64 = 2 * (NAV_PRECISION as u64);
+const PAYMENT: u64 = 100;
+
+struct RedemptionAccounts {
+    investor: TestAccount,
+    fund: TestAccount,
+    mint: TestAccount,
+    payment_mint: TestAccount,
+    nav_record: TestAccount,
+    request: TestAccount,
+    token_account: TestAccount,
+    payment_account: TestAccount,
+    payment_vault: TestAccount,
+    bond: TestAccount,
+    bond_holding: TestAccount,
+    token_program: TestAccount,
+}
+
+impl RedemptionAccounts {
+    // A request for `shares` at a strike where `redeemable` of `redemptions` shares were paid for
+    fn new(shares: u64, redemptions: u64, redeemable: u64) -> Self {
+        let investor = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let payment_mint = Pubkey::new_unique();
+        let (fund, fund_bump) = Pubkey::find_program_address(&[b"fund", mint.as_ref()], &PROGRAM_ID);
+        let (nav_record, nav_bump) = Pubkey::find_program_address(
+            &[b"nav", fund.as_ref(), 0u64.to_le_bytes().as_ref()],
+            &PROGRAM_ID
+        );
+        let (request, request_bump) = Pubkey::find_program_address(
+            &[b"redemption", fund.as_ref(), investor.as_ref(), 0u64.to_le_bytes().as_ref()],
+            &PROGRAM_ID
+        );
+        let token_account = Pubkey::new_unique();
+        let payment_account = Pubkey::new_unique();
+        let payment_vault = get_associated_token_address_with_program_id(
+            &fund,
+            &payment_mint,
+            &spl_token_2022::ID
+        );
+
+        RedemptionAccounts {
+            investor: TestAccount::wallet(investor).writable(),
+            fund: TestAccount::anchor(fund, &Fund {
+                mint,
+                payment_mint,
+                nav_publisher: Pubkey::new_unique(),
+                current_epoch: 1,
+                pending_subscriptions: 0,
+                pending_redemptions: 0,
+                reserved: payment_for(redeemable, NAV).unwrap(),
+                bump: fund_bump,
+            }).writable(),
+            mint: hooked_mint(mint, &Pubkey::new_unique(), None).writable(),
+            payment_mint: hooked_mint(payment_mint, &Pubkey::new_unique(), None),
+            nav_record: TestAccount::anchor(nav_record, &NavRecord {
+                fund,
+                epoch: 0,
+                nav: NAV,
+                struck_at: NOW,
+                subscriptions: 0,
+                redemptions,
+                redeemable,
+                reserved: payment_for(redeemable, NAV).unwrap(),
+                unsettled: redemptions,
+                bump: nav_bump,
+            }).writable(),
+            request: TestAccount::anchor(request, &FundRequest {
+                fund,
+                investor,
+                epoch: 0,
+                kind: FundRequestKind::Redemption,
+                amount: shares,
+                token_account,
+                payment_account,
+                bump: request_bump,
+            }).writable(),
+            token_account: with_delegate(
+                &token_account_of(token_account, &mint, &investor, shares),
+                &fund,
+                shares
+            ).writable(),
+            payment_account: token_account_of(payment_account, &payment_mint, &investor, 0).writable(),
+            payment_vault: token_account_of(payment_vault, &payment_mint, &fund, 1_000_000).writable(),
+            bond: empty(pda(&[b"bond", mint.as_ref()])),
+            bond_holding: empty(pda(&[b"bond_holding", token_account.as_ref()])).writable(),
+            token_program: TestAccount::program(spl_token_2022::ID),
+        }
+    }
+
+    fn settle(&mut self) -> ProgramResult {
+        let data = (instruction::SettleRedemption {}).data();
+        let mut payment_token_program = TestAccount::program(spl_token_2022::ID);
+        process(
+            &mut [
+                &mut self.investor,
+                &mut self.fund,
+                &mut self.mint,
+                &mut self.payment_mint,
+                &mut self.nav_record,
+                &mut self.request,
+                &mut self.token_account,
+                &mut self.payment_account,
+                &mut self.payment_vault,
+                &mut self.bond,
+                &mut self.bond_holding,
+                &mut self.token_program,
+                &mut payment_token_program,
+            ],
+            &data
+        )
+    }
+}
+
+struct SubscriptionAccounts {
+    mint_authority: TestAccount,
+    investor: TestAccount,
+    fund: TestAccount,
+    mint: TestAccount,
+    payment_mint: TestAccount,
+    nav_record: TestAccount,
+    request: TestAccount,
+    token_account: TestAccount,
+    payment_account: TestAccount,
+    payment_vault: TestAccount,
+    holding_limits: TestAccount,
+    bond: TestAccount,
+    bond_holding: TestAccount,
+    token_program: TestAccount,
+    payment_token_program: TestAccount,
+}
+
+impl SubscriptionAccounts {
+    // A request paying `PAYMENT` in epoch 0, struck at `NAV` unless `struck` is false
+    fn new(struck: bool) -> Self {
+        let investor = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let payment_mint = Pubkey::new_unique();
+        let (fund, fund_bump) = Pubkey::find_program_address(&[b"fund", mint.as_ref()], &PROGRAM_ID);
+        let (nav_record, nav_bump) = Pubkey::find_program_address(
+            &[b"nav", fund.as_ref(), 0u64.to_le_bytes().as_ref()],
+            &PROGRAM_ID
+        );
+        let (request, request_bump) = Pubkey::find_program_address(
+            &[b"subscription", fund.as_ref(), investor.as_ref(), 0u64.to_le_bytes().as_ref()],
+            &PROGRAM_ID
+        );
+        let token_account = Pubkey::new_unique();
+        let payment_account = Pubkey::new_unique();
+        let payment_vault = get_associated_token_address_with_program_id(
+            &fund,
+            &payment_mint,
+            &spl_token_2022::ID
+        );
+
+        SubscriptionAccounts {
+            mint_authority: TestAccount::wallet(Pubkey::new_unique()).signer(),
+            investor: TestAccount::wallet(investor).signer().writable(),
+            fund: TestAccount::anchor(fund, &Fund {
+                mint,
+                payment_mint,
+                nav_publisher: Pubkey::new_unique(),
+                current_epoch: u64::from(struck),
+                pending_subscriptions: if struck { 0 } else { PAYMENT },
+                pending_redemptions: 0,
+                reserved: if struck { PAYMENT } else { 0 },
+                bump: fund_bump,
+            }).writable(),
+            mint: hooked_mint(mint, &Pubkey::new_unique(), None).writable(),
+            payment_mint: hooked_mint(payment_mint, &Pubkey::new_unique(), None),
+            nav_record: TestAccount::anchor(nav_record, &NavRecord {
+                fund,
+                epoch: 0,
+                nav: NAV,
+                struck_at: NOW,
+                subscriptions: PAYMENT,
+                redemptions: 0,
+                redeemable: 0,
+                reserved: 0,
+                unsettled: 0,
+                bump: nav_bump,
+            }),
+            request: TestAccount::anchor(request, &FundRequest {
+                fund,
+                investor,
+                epoch: 0,
+                kind: FundRequestKind::Subscription,
+                amount: PAYMENT,
+                token_account,
+                payment_account,
+                bump: request_bump,
+            }).writable(),
+            token_account: token_account_of(token_account, &mint, &investor, 0).writable(),
+            payment_account: token_account_of(payment_account, &payment_mint, &investor, 0).writable(),
+            payment_vault: token_account_of(payment_vault, &payment_mint, &fund, PAYMENT).writable(),
+            holding_limits: empty(pda(&[b"holding_limits", mint.as_ref()])),
+            bond: empty(pda(&[b"bond", mint.as_ref()])),
+            bond_holding: empty(pda(&[b"bond_holding", token_account.as_ref()])).writable(),
+            token_program: TestAccount::program(spl_token_2022::ID),
+            payment_token_program: TestAccount::program(spl_token_2022::ID),
+        }
+    }
+
+    fn set_max_balance(&mut self, max_balance: u64) {
+        let mint = self.mint.key();
+        self.holding_limits = TestAccount::anchor(self.holding_limits.key(), &HoldingLimits {
+            mint,
+            max_balance,
+            max_supply_bps: 0,
+            supply_basis: 0,
+            exempt: Vec::new(),
+            bump: 0,
+        });
+    }
+
+    // Gives the mint bond terms at `coupon_index` and the investor `balance` tokens
+    // registered for coupons from index zero
+    fn hold_coupon_bearing(&mut self, balance: u64, coupon_index: u128) {
+        let mint = self.mint.key();
+        let investor = self.investor.key();
+        self.token_account = token_account_of(self.token_account.key(), &mint, &investor, balance).writable();
+        self.bond = TestAccount::anchor(self.bond.key(), &Bond {
+            mint,
+            payment_mint: self.payment_mint.key(),
+            face_value: 1,
+            coupon_rate_bps: 0,
+            coupon_interval: 1,
+            start_ts: 0,
+            maturity_ts: 1,
+            periods_accrued: 0,
+            coupon_index,
+            bump: 0,
+        });
+        self.bond_holding = TestAccount::anchor(self.bond_holding.key(), &BondHolding {
+            token_account: self.token_account.key(),
+            balance,
+            index_checkpoint: 0,
+            accrued: 0,
+            bump: 0,
+        }).writable();
+    }
+
+    fn settle(&mut self) -> ProgramResult {
+        let data = (instruction::SettleSubscription {}).data();
+        process(
+            &mut [
+                &mut self.mint_authority,
+                &mut self.investor,
+                &mut self.fund,
+                &mut self.mint,
+                &mut self.payment_mint,
+                &mut self.nav_record,
+                &mut self.request,
+                &mut self.token_account,
+                &mut self.payment_account,
+                &mut self.payment_vault,
+                &mut self.holding_limits,
+                &mut self.bond,
+                &mut self.bond_holding,
+                &mut self.token_program,
+                &mut self.payment_token_program,
+            ],
+            &data
+        )
+    }
+
+    fn cancel(&mut self) -> ProgramResult {
+        let data = (instruction::CancelSubscription {}).data();
+        process(
+            &mut [
+                &mut self.investor,
+                &mut self.fund,
+                &mut self.payment_mint,
+                &mut self.request,
+                &mut self.payment_account,
+                &mut self.payment_vault,
+                &mut self.payment_token_program,
+            ],
+            &data
+        )
+    }
+}
+
+// Amounts of the mint and refund CPIs of a subscription
+fn minted_and_refunded() -> (Option<u64>, Option<u64>) {
+    let invoked = invoked();
+    let minted = invoked.iter().find_map(|ix| match TokenInstruction::unpack(&ix.data) {
+        Ok(TokenInstruction::MintTo { amount }) => Some(amount),
+        _ => None,
+    });
+    let refunded = invoked.iter().find_map(|ix| match TokenInstruction::unpack(&ix.data) {
+        Ok(TokenInstruction::TransferChecked { amount, .. }) => Some(amount),
+        _ => None,
+    });
+    (minted, refunded)
+}
+
+fn token_account_of(key: Pubkey, mint: &Pubkey, owner: &Pubkey, amount: u64) -> TestAccount {
+    token_account(key, mint, owner, amount, false)
+}
+
+// Amounts of the burn and payment CPIs of a settlement
+fn settled_amounts() -> (u64, u64) {
+    let invoked = invoked();
+    let burned = invoked.iter().find_map(|ix| match TokenInstruction::unpack(&ix.data) {
+        Ok(TokenInstruction::Burn { amount }) => Some(amount),
+        _ => None,
+    });
+    let paid = invoked.iter().find_map(|ix| match TokenInstruction::unpack(&ix.data) {
+        Ok(TokenInstruction::TransferChecked { amount, .. }) => Some(amount),
+        _ => None,
+    });
+    (burned.unwrap_or_default(), paid.unwrap_or_default())
+}
+
+#[test]
+fn redemption_is_pro_rated_when_liquidity_is_short() {
+    // Half of the epoch's redemptions could be paid for
+    let mut accounts = RedemptionAccounts::new(40, 100, 50);
+
+    accounts.settle().unwrap();
+
+    assert_eq!(settled_amounts(), (20, 40));
+    let fund: Fund = accounts.fund.deserialize();
+    assert_eq!(fund.reserved, payment_for(50 - 20, NAV).unwrap());
+    assert_eq!(accounts.request.lamports, 0, "request is closed");
+}
+
+#[test]
+fn fully_funded_redemption_is_paid_in_full() {
+    let mut accounts = RedemptionAccounts::new(40, 100, 100);
+
+    accounts.settle().unwrap();
+
+    assert_eq!(settled_amounts(), (40, 80));
+    let fund: Fund = accounts.fund.deserialize();
+    assert_eq!(fund.reserved, payment_for(60, NAV).unwrap());
+}
+
+#[test]
+fn shares_moved_away_after_the_request_are_not_paid() {
+    let mut accounts = RedemptionAccounts::new(40, 100, 100);
+    let (key, mint, investor, fund) = (
+        accounts.token_account.key(),
+        accounts.mint.key(),
+        accounts.investor.key(),
+        accounts.fund.key(),
+    );
+    accounts.token_account = with_delegate(&token_account_of(key, &mint, &investor, 5), &fund, 40).writable();
+
+    accounts.settle().unwrap();
+
+    assert_eq!(settled_amounts(), (5, 10));
+    // The whole reservation of the request is released
+    let fund: Fund = accounts.fund.deserialize();
+    assert_eq!(fund.reserved, payment_for(60, NAV).unwrap());
+}
+
+#[test]
+fn last_redemption_settlement_releases_rounding_dust() {
+    // A third of a share is not paid, but the strike reserved a whole one for it
+    let mut accounts = RedemptionAccounts::new(1, 3, 2);
+    let mut nav_record: NavRecord = accounts.nav_record.deserialize();
+    nav_record.unsettled = 1;
+    accounts.nav_record = TestAccount::anchor(accounts.nav_record.key(), &nav_record).writable();
+
+    accounts.settle().unwrap();
+
+    assert_eq!(settled_amounts(), (0, 0));
+    let fund: Fund = accounts.fund.deserialize();
+    assert_eq!(fund.reserved, 0);
+    let nav_record: NavRecord = accounts.nav_record.deserialize();
+    assert_eq!((nav_record.reserved, nav_record.unsettled), (0, 0));
+}
+
+#[test]
+fn strike_fills_every_redemption_when_liquidity_suffices() {
+    assert_eq!(redemption_fill(100, NAV, 200), Some((100, 200)));
+    assert_eq!(redemption_fill(100, NAV, 1_000), Some((100, 200)));
+}
+
+#[test]
+fn strike_pro_rates_redemptions_when_liquidity_is_short() {
+    // 200 is owed for 100 shares, 50 is available
+    assert_eq!(redemption_fill(100, NAV, 50), Some((25, 50)));
+    assert_eq!(redemption_fill(100, NAV, 0), Some((0, 0)));
+
+    // Rounded down, never reserving more than is available
+    let nav = (NAV_PRECISION as u64) * 3 / 2;
+    assert_eq!(redemption_fill(3, nav, 3), Some((2, 3)));
+    assert_eq!(redemption_fill(7, NAV, 5), Some((2, 4)));
+}
+
+#[test]
+fn subscription_within_holding_limits_mints_shares() {
+    let mut accounts = SubscriptionAccounts::new(true);
+
+    accounts.settle().unwrap();
+
+    assert_eq!(minted_and_refunded(), (shares_for(PAYMENT, NAV), None));
+    let fund: Fund = accounts.fund.deserialize();
+    assert_eq!(fund.reserved, 0, "the payment becomes proceeds");
+    assert_eq!(accounts.request.lamports, 0, "request is closed");
+}
+
+#[test]
+fn settled_subscription_syncs_the_bond_holding() {
+    let mut accounts = SubscriptionAccounts::new(true);
+    accounts.hold_coupon_bearing(10, 2 * COUPON_INDEX_PRECISION);
+
+    accounts.settle().unwrap();
+
+    let holding: BondHolding = accounts.bond_holding.deserialize();
+    assert_eq!(holding.accrued, 20, "coupons on the balance held before the mint");
+    assert_eq!(holding.balance, 10 + shares_for(PAYMENT, NAV).unwrap());
+    assert_eq!(holding.index_checkpoint, 2 * COUPON_INDEX_PRECISION);
+}
+
+#[test]
+fn subscription_breaching_holding_limits_is_refunded() {
+    let mut accounts = SubscriptionAccounts::new(true);
+    accounts.set_max_balance(10);
+
+    accounts.settle().unwrap();
+
+    assert_eq!(minted_and_refunded(), (None, Some(PAYMENT)));
+    let fund: Fund = accounts.fund.deserialize();
+    assert_eq!(fund.reserved, 0);
+    assert_eq!(accounts.request.lamports, 0, "request is closed");
+}
+
+#[test]
+fn subscription_can_be_cancelled_before_the_strike() {
+    let mut accounts = SubscriptionAccounts::new(false);
+
+    accounts.cancel().unwrap();
+
+    assert_eq!(minted_and_refunded(), (None, Some(PAYMENT)));
+    let fund: Fund = accounts.fund.deserialize();
+    assert_eq!(fund.pending_subscriptions, 0);
+    assert_eq!(accounts.request.lamports, 0, "request is closed");
+}
+
+#[test]
+fn struck_subscription_cannot_be_cancelled() {
+    let mut accounts = SubscriptionAccounts::new(true);
+
+    let result = accounts.cancel();
+
+    assert_eq!(result.unwrap_err(), program_error(FundError::AlreadyStruck));
+}
+
+#[test]
+fn nav_conversions_round_down() {
+    let nav_record = NavRecord {
+        fund: Pubkey::default(),
+        epoch: 0,
+        nav: NAV,
+        struck_at: NOW,
+        subscriptions: 0,
+        redemptions: 3,
+        redeemable: 2,
+        reserved: 4,
+        unsettled: 3,
+        bump: 0,
+    };
+    assert_eq!(nav_record.filled_shares(1), 0);
+    assert_eq!(nav_record.filled_shares(2), 1);
+    assert_eq!(shares_for(5, NAV), Some(2));
+    assert_eq!(payment_for(3, NAV), Some(6));
+    assert_eq!(shares_for(1, 0), None);
+}
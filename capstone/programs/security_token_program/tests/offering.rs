@@ -0,0 +1,201 @@
+mod common;
+
+use anchor_lang::{ prelude::*, solana_program::entrypoint::ProgramResult, InstructionData };
+use anchor_spl::{
+    associated_token::{ self, get_associated_token_address_with_program_id },
+    token_2022::spl_token_2022::{ self, instruction::TokenInstruction },
+};
+use common::*;
+use security_token_program::{
+    instruction,
+    Config,
+    HoldingLimits,
+    Registry,
+    SaleStatus,
+    SecurityStatus,
+    Subscription,
+    ID as PROGRAM_ID,
+};
+
+const MAX_BALANCE: u64 = 100;
+
+// A succeeded escrowed offering, settled for the subscriptions in `settlements`
+struct OfferingAccounts {
+    owner: TestAccount,
+    mint_authority: TestAccount,
+    token_mint: TestAccount,
+    registry: TestAccount,
+    config: TestAccount,
+    payment_vault: TestAccount,
+    treasury: TestAccount,
+    payment_mint: TestAccount,
+    holding_limits: TestAccount,
+    bond: TestAccount,
+    token_program: TestAccount,
+    payment_token_program: TestAccount,
+    associated_token_program: TestAccount,
+    settlements: Vec<TestAccount>,
+}
+
+impl OfferingAccounts {
+    fn new() -> Self {
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let payment_mint = Pubkey::new_unique();
+        let (registry, registry_bump) = Pubkey::find_program_address(&[b"registry", mint.as_ref()], &PROGRAM_ID);
+        let (config, config_bump) = Pubkey::find_program_address(&[b"config", mint.as_ref()], &PROGRAM_ID);
+        let payment_vault = get_associated_token_address_with_program_id(
+            &config,
+            &payment_mint,
+            &spl_token_2022::ID
+        );
+
+        OfferingAccounts {
+            owner: TestAccount::wallet(owner).signer(),
+            mint_authority: TestAccount::wallet(owner).signer(),
+            token_mint: hooked_mint(mint, &owner, None).writable(),
+            registry: TestAccount::anchor(registry, &Registry {
+                mint,
+                admin: owner,
+                status: SecurityStatus::Active,
+                extra_account_meta_list: pda(&[b"extra-account-metas", mint.as_ref()]),
+                white_list: pda(&[b"white_list", mint.as_ref()]),
+                kyc_providers: pda(&[b"kyc_providers", mint.as_ref()]),
+                config,
+                fee_config: Pubkey::default(),
+                bump: registry_bump,
+            }),
+            config: TestAccount::anchor(config, &Config {
+                owner,
+                mint,
+                compliance_key: Pubkey::new_unique(),
+                max_supply: 10_000,
+                total_sold: 0,
+                tranches: Vec::new(),
+                escrow_payments: true,
+                payment_mint,
+                soft_cap: 0,
+                close_ts: NOW,
+                total_raised: 0,
+                status: SaleStatus::Succeeded,
+                bump: config_bump,
+            }).writable(),
+            payment_vault: token_account(payment_vault, &payment_mint, &config, 1_000, false).writable(),
+            treasury: token_account(Pubkey::new_unique(), &payment_mint, &owner, 0, false).writable(),
+            payment_mint: hooked_mint(payment_mint, &Pubkey::new_unique(), None),
+            holding_limits: TestAccount::anchor(pda(&[b"holding_limits", mint.as_ref()]), &HoldingLimits {
+                mint,
+                max_balance: MAX_BALANCE,
+                max_supply_bps: 0,
+                supply_basis: 0,
+                exempt: Vec::new(),
+                bump: 0,
+            }),
+            bond: empty(pda(&[b"bond", mint.as_ref()])),
+            token_program: TestAccount::program(spl_token_2022::ID),
+            payment_token_program: TestAccount::program(spl_token_2022::ID),
+            associated_token_program: TestAccount::program(associated_token::ID),
+            settlements: Vec::new(),
+        }
+    }
+
+    // Adds the triple of a subscription of `tokens` for `paid`, to a recipient already holding `held`
+    fn subscription(&mut self, tokens: u64, paid: u64, held: u64) -> usize {
+        let investor = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let config = self.config.key();
+        let (subscription, bump) = Pubkey::find_program_address(
+            &[b"subscription", config.as_ref(), investor.as_ref()],
+            &PROGRAM_ID
+        );
+
+        self.settlements.push(
+            TestAccount::anchor(subscription, &Subscription {
+                config,
+                investor,
+                recipient,
+                tokens,
+                paid,
+                settled: false,
+                refundable: false,
+                bump,
+            }).writable()
+        );
+        self.settlements.push(token_account(recipient, &self.token_mint.key(), &investor, held, false).writable());
+        self.settlements.push(empty(pda(&[b"bond_holding", recipient.as_ref()])).writable());
+        self.settlements.len() - 3
+    }
+
+    fn finalize(&mut self) -> ProgramResult {
+        let data = (instruction::FinalizeOffering {}).data();
+        let mut accounts: Vec<&mut TestAccount> = vec![
+            &mut self.owner,
+            &mut self.mint_authority,
+            &mut self.token_mint,
+            &mut self.registry,
+            &mut self.config,
+            &mut self.payment_vault,
+            &mut self.treasury,
+            &mut self.payment_mint,
+            &mut self.holding_limits,
+            &mut self.bond,
+            &mut self.token_program,
+            &mut self.payment_token_program,
+            &mut self.associated_token_program
+        ];
+        accounts.extend(self.settlements.iter_mut());
+        process(&mut accounts, &data)
+    }
+}
+
+// Amounts of the mint and payment CPIs of a settlement, in order
+fn minted_and_released() -> (Vec<u64>, Vec<u64>) {
+    let invoked = invoked();
+    let minted = invoked
+        .iter()
+        .filter_map(|ix| match TokenInstruction::unpack(&ix.data) {
+            Ok(TokenInstruction::MintTo { amount }) => Some(amount),
+            _ => None,
+        })
+        .collect();
+    let released = invoked
+        .iter()
+        .filter_map(|ix| match TokenInstruction::unpack(&ix.data) {
+            Ok(TokenInstruction::TransferChecked { amount, .. }) => Some(amount),
+            _ => None,
+        })
+        .collect();
+    (minted, released)
+}
+
+#[test]
+fn settling_releases_each_payment_to_the_treasury() {
+    let mut accounts = OfferingAccounts::new();
+    let first = accounts.subscription(40, 400, 0);
+    let second = accounts.subscription(60, 600, 40);
+
+    accounts.finalize().unwrap();
+
+    assert_eq!(minted_and_released(), (vec![40, 60], vec![400, 600]));
+    for index in [first, second] {
+        let subscription: Subscription = accounts.settlements[index].deserialize();
+        assert!(subscription.settled);
+    }
+}
+
+#[test]
+fn subscription_over_the_holding_limits_is_left_refundable() {
+    let mut accounts = OfferingAccounts::new();
+    let over = accounts.subscription(60, 600, MAX_BALANCE - 59);
+    let within = accounts.subscription(40, 400, 0);
+
+    accounts.finalize().unwrap();
+
+    // The rest of the batch still settles, the breaching payment stays in escrow
+    assert_eq!(minted_and_released(), (vec![40], vec![400]));
+    let subscription: Subscription = accounts.settlements[over].deserialize();
+    assert!(subscription.refundable);
+    assert!(!subscription.settled);
+    let subscription: Subscription = accounts.settlements[within].deserialize();
+    assert!(subscription.settled);
+}
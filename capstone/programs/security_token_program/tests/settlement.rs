@@ -0,0 +1,100 @@
+mod common;
+
+use anchor_lang::{ prelude::*, solana_program::instruction::Instruction, Discriminator, InstructionData };
+use anchor_spl::token_2022::spl_token_2022;
+use common::*;
+use security_token_program::{ instruction, settlement::require_next_transfer, MarketError, ID as PROGRAM_ID };
+
+struct Settlement {
+    source: Pubkey,
+    mint: Pubkey,
+    destination: Pubkey,
+}
+
+impl Settlement {
+    fn new() -> Self {
+        Settlement {
+            source: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+        }
+    }
+
+    fn transfer(&self, amount: u64) -> Instruction {
+        spl_token_2022::instruction::transfer_checked(
+            &spl_token_2022::ID,
+            &self.source,
+            &self.mint,
+            &self.destination,
+            &Pubkey::new_unique(),
+            &[],
+            amount,
+            0
+        ).unwrap()
+    }
+
+    // Checks a fill running as the first instruction of a transaction made of `instructions`
+    fn check(&self, instructions: &[Instruction]) -> Result<()> {
+        setup();
+        let mut sysvar = instructions_sysvar(instructions, 0);
+        require_next_transfer(
+            &sysvar.account_info(),
+            &instruction::FillOrder::DISCRIMINATOR,
+            &self.source,
+            &self.mint,
+            &self.destination,
+            10
+        )
+    }
+}
+
+fn fill_order() -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: Vec::new(),
+        data: (instruction::FillOrder { amount: 10 }).data(),
+    }
+}
+
+fn assert_not_top_level(result: Result<()>) {
+    let error: ProgramError = result.unwrap_err().into();
+    assert_eq!(error, program_error(MarketError::NotTopLevel));
+}
+
+#[test]
+fn fill_followed_by_its_transfer_settles() {
+    let settlement = Settlement::new();
+
+    settlement.check(&[fill_order(), settlement.transfer(10)]).unwrap();
+}
+
+#[test]
+fn fill_by_cpi_is_rejected() {
+    // A program calling `fill_order` twice could pair both calls with one transfer
+    let settlement = Settlement::new();
+    let instructions = [
+        Instruction {
+            program_id: Pubkey::new_unique(),
+            accounts: Vec::new(),
+            data: Vec::new(),
+        },
+        settlement.transfer(10),
+    ];
+    set_stack_height(2);
+    let result = settlement.check(&instructions);
+    set_stack_height(1);
+
+    assert_not_top_level(result);
+}
+
+#[test]
+fn transfer_must_follow_the_expected_instruction() {
+    let settlement = Settlement::new();
+    let cancel_order = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: Vec::new(),
+        data: instruction::CancelOrder {}.data(),
+    };
+
+    assert_not_top_level(settlement.check(&[cancel_order, settlement.transfer(10)]));
+}
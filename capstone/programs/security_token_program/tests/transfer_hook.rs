@@ -0,0 +1,760 @@
+mod common;
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::instruction::Instruction,
+    system_program,
+    InstructionData,
+    ToAccountMetas,
+};
+use anchor_spl::token_2022::spl_token_2022::{ self, instruction::TokenInstruction };
+use common::*;
+use security_token_program::{
+    accounts,
+    instruction,
+    Attestation,
+    Config,
+    HoldingLimitError,
+    HoldingLimits,
+    HookAccountAddress,
+    HookAccountConfig,
+    KycProviders,
+    MemoError,
+    MemoPolicy,
+    OrderSide,
+    Registry,
+    RuleModule,
+    RuleModuleError,
+    RuleModules,
+    SaleStatus,
+    SecurityStatus,
+    SignedApproval,
+    TransferError,
+    WhiteList,
+    ID as PROGRAM_ID,
+};
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+// Accounts of a `transfer_hook` call, in the order Token-2022 passes them
+struct HookAccounts {
+    sender: Pubkey,
+    recipient: Pubkey,
+    source: TestAccount,
+    mint: TestAccount,
+    destination: TestAccount,
+    owner: TestAccount,
+    extra_account_meta_list: TestAccount,
+    registry: TestAccount,
+    white_list: TestAccount,
+    kyc_providers: TestAccount,
+    attestation: TestAccount,
+    approval: TestAccount,
+    bond: TestAccount,
+    source_holding: TestAccount,
+    destination_holding: TestAccount,
+    holding_limits: TestAccount,
+    rule_modules: TestAccount,
+    memo_policy: TestAccount,
+    instructions_sysvar: TestAccount,
+    config: TestAccount,
+    // Issuer-configured accounts, passed after the built-in ones
+    configured: Vec<TestAccount>,
+}
+
+impl HookAccounts {
+    fn new(transferring: bool) -> Self {
+        HookAccounts::with_permanent_delegate(transferring, None)
+    }
+
+    fn with_permanent_delegate(transferring: bool, permanent_delegate: Option<Pubkey>) -> Self {
+        let authority = Pubkey::new_unique();
+        let sender = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let source = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+
+        let (registry, registry_bump) = Pubkey::find_program_address(
+            &[b"registry", mint.as_ref()],
+            &PROGRAM_ID
+        );
+        let extra_account_meta_list = pda(&[b"extra-account-metas", mint.as_ref()]);
+        let white_list = pda(&[b"white_list", mint.as_ref()]);
+        let kyc_providers = pda(&[b"kyc_providers", mint.as_ref()]);
+
+        HookAccounts {
+            sender,
+            recipient,
+            source: token_account(source, &mint, &sender, 99, transferring),
+            mint: hooked_mint(mint, &authority, permanent_delegate),
+            destination: token_account(destination, &mint, &recipient, 1, false),
+            owner: TestAccount::wallet(sender),
+            extra_account_meta_list: common::extra_account_meta_list(extra_account_meta_list, &[]),
+            registry: TestAccount::anchor(registry, &Registry {
+                mint,
+                admin: authority,
+                status: SecurityStatus::Active,
+                extra_account_meta_list,
+                white_list,
+                kyc_providers,
+                config: Pubkey::default(),
+                fee_config: Pubkey::default(),
+                bump: registry_bump,
+            }),
+            white_list: TestAccount::anchor(white_list, &WhiteList {
+                authority,
+                white_list: Vec::new(),
+            }),
+            kyc_providers: TestAccount::anchor(kyc_providers, &KycProviders {
+                authority,
+                providers: Vec::new(),
+            }),
+            attestation: empty(pda(&[b"attestation", mint.as_ref(), recipient.as_ref()])),
+            approval: empty(pda(&[b"approval", mint.as_ref(), recipient.as_ref()])),
+            bond: empty(pda(&[b"bond", mint.as_ref()])),
+            source_holding: empty(pda(&[b"bond_holding", source.as_ref()])).writable(),
+            destination_holding: empty(pda(&[b"bond_holding", destination.as_ref()])).writable(),
+            holding_limits: empty(pda(&[b"holding_limits", mint.as_ref()])),
+            rule_modules: empty(pda(&[b"rule_modules", mint.as_ref()])),
+            memo_policy: empty(pda(&[b"memo_policy", mint.as_ref()])),
+            instructions_sysvar: instructions_sysvar(&[transfer_instruction(&source, &mint, &destination)], 0),
+            config: empty(pda(&[b"config", mint.as_ref()])),
+            configured: Vec::new(),
+        }
+    }
+
+    fn whitelist_destination(&mut self) {
+        let mut white_list: WhiteList = self.white_list.deserialize();
+        white_list.white_list.push(self.destination.key());
+        self.white_list = TestAccount::anchor(self.white_list.key(), &white_list);
+    }
+
+    fn set_status(&mut self, status: SecurityStatus) {
+        let mut registry: Registry = self.registry.deserialize();
+        registry.status = status;
+        self.registry = TestAccount::anchor(self.registry.key(), &registry);
+    }
+
+    fn set_holding_limits(
+        &mut self,
+        max_balance: u64,
+        max_supply_bps: u16,
+        supply_basis: u64,
+        exempt: Vec<Pubkey>
+    ) {
+        let mint = self.mint.key();
+        self.holding_limits = TestAccount::anchor(self.holding_limits.key(), &HoldingLimits {
+            mint,
+            max_balance,
+            max_supply_bps,
+            supply_basis,
+            exempt,
+            bump: 0,
+        });
+    }
+
+    fn set_rule_modules(&mut self, modules: Vec<RuleModule>, configured: Vec<TestAccount>) {
+        let mint = self.mint.key();
+        self.rule_modules = TestAccount::anchor(self.rule_modules.key(), &RuleModules {
+            mint,
+            modules,
+            bump: 0,
+        });
+        self.configured = configured;
+    }
+
+    // Runs `set_rule_modules` as the registry admin
+    fn configure_rule_modules(&mut self, modules: Vec<RuleModule>) -> Result<()> {
+        let mint = self.mint.key();
+        // `init_if_needed` expects an existing account at its full size
+        let mut data = Vec::new();
+        (RuleModules { mint, modules: Vec::new(), bump: 0 }).try_serialize(&mut data).unwrap();
+        data.resize(8 + RuleModules::INIT_SPACE, 0);
+        let mut rule_modules = TestAccount::new(self.rule_modules.key(), PROGRAM_ID, &data).writable();
+        let registry: Registry = self.registry.deserialize();
+        let mut admin = TestAccount::wallet(registry.admin).signer().writable();
+        let mut audit_log = audit_log(pda(&[b"audit_log", mint.as_ref()]), &mint).writable();
+        let mut system_program = TestAccount::program(system_program::ID);
+
+        let data = (instruction::SetRuleModules { modules, reason_hash: [0; 32] }).data();
+        process(
+            &mut [
+                &mut admin,
+                &mut self.registry,
+                &mut self.extra_account_meta_list,
+                &mut rule_modules,
+                &mut audit_log,
+                &mut system_program,
+            ],
+            &data
+        )?;
+        self.rule_modules = rule_modules;
+        Ok(())
+    }
+
+    // Approval of the recipient signed by `signer`, with `compliance_key` as the current key
+    fn approve_recipient(&mut self, signer: Pubkey, compliance_key: Pubkey) {
+        let mint = self.mint.key();
+        self.approval = TestAccount::anchor(self.approval.key(), &SignedApproval {
+            wallet: self.recipient,
+            mint,
+            expires_at: NOW + 60,
+            signer,
+            bump: 0,
+        });
+        self.config = TestAccount::anchor(self.config.key(), &Config {
+            owner: Pubkey::new_unique(),
+            mint,
+            compliance_key,
+            max_supply: 1_000,
+            total_sold: 0,
+            tranches: Vec::new(),
+            escrow_payments: false,
+            payment_mint: Pubkey::default(),
+            soft_cap: 0,
+            close_ts: 0,
+            total_raised: 0,
+            status: SaleStatus::Open,
+            bump: 0,
+        });
+    }
+
+    fn require_memos(&mut self, reason_codes: &[&str]) {
+        let mint = self.mint.key();
+        self.memo_policy = TestAccount::anchor(self.memo_policy.key(), &MemoPolicy {
+            mint,
+            required: true,
+            reason_codes: reason_codes.iter().map(|code| code.to_string()).collect(),
+            bump: 0,
+        });
+    }
+
+    // Places `instructions` before the transfer in its transaction
+    fn precede_transfer_with(&mut self, mut instructions: Vec<Instruction>) {
+        let current_index = instructions.len() as u16;
+        instructions.push(transfer_instruction(&self.source.key(), &self.mint.key(), &self.destination.key()));
+        self.instructions_sysvar = instructions_sysvar(&instructions, current_index);
+    }
+
+    fn execute(&mut self, amount: u64) -> Result<()> {
+        let data = (instruction::TransferHook { amount }).data();
+        let mut accounts: Vec<&mut TestAccount> = vec![
+                &mut self.source,
+                &mut self.mint,
+                &mut self.destination,
+                &mut self.owner,
+                &mut self.extra_account_meta_list,
+                &mut self.registry,
+                &mut self.white_list,
+                &mut self.kyc_providers,
+                &mut self.attestation,
+                &mut self.approval,
+                &mut self.bond,
+                &mut self.source_holding,
+                &mut self.destination_holding,
+                &mut self.holding_limits,
+                &mut self.rule_modules,
+                &mut self.memo_policy,
+                &mut self.instructions_sysvar,
+                &mut self.config
+        ];
+        accounts.extend(self.configured.iter_mut());
+        process(&mut accounts, &data).map_err(Into::into)
+    }
+}
+
+fn transfer_instruction(source: &Pubkey, mint: &Pubkey, destination: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: spl_token_2022::ID,
+        accounts: vec![
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(*destination, false),
+        ],
+        data: (TokenInstruction::TransferChecked { amount: 1, decimals: 0 }).pack(),
+    }
+}
+
+fn memo(text: &str) -> Instruction {
+    Instruction {
+        program_id: spl_memo::id(),
+        accounts: Vec::new(),
+        data: text.as_bytes().to_vec(),
+    }
+}
+
+// `post_order` escrowing into `security_vault`, with its other accounts left arbitrary
+fn post_order(side: OrderSide, security_vault: Pubkey) -> Instruction {
+    let accounts = accounts::PostOrder {
+        maker: Pubkey::new_unique(),
+        registry: Pubkey::new_unique(),
+        market: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        payment_mint: Pubkey::new_unique(),
+        maker_token_account: Pubkey::new_unique(),
+        maker_payment_account: Pubkey::new_unique(),
+        order: Pubkey::new_unique(),
+        security_vault,
+        payment_vault: Pubkey::new_unique(),
+        freeze_authority: Pubkey::new_unique(),
+        white_list: Pubkey::new_unique(),
+        kyc_providers: Pubkey::new_unique(),
+        attestation: Pubkey::new_unique(),
+        investor_class: None,
+        instructions_sysvar: Pubkey::new_unique(),
+        token_program: Pubkey::new_unique(),
+        payment_token_program: Pubkey::new_unique(),
+        associated_token_program: Pubkey::new_unique(),
+        system_program: Pubkey::new_unique(),
+    };
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: (instruction::PostOrder { nonce: 0, side, price: 1, amount: 1 }).data(),
+    }
+}
+
+#[test]
+fn extra_account_metas_resolve_to_program_accounts() {
+    setup();
+    let mut hook = HookAccounts::new(true);
+    let meta_list_data = hook.extra_account_meta_list.data().to_vec();
+
+    let mut execute = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(hook.source.key(), false),
+            AccountMeta::new_readonly(hook.mint.key(), false),
+            AccountMeta::new_readonly(hook.destination.key(), false),
+            AccountMeta::new_readonly(hook.owner.key(), false),
+            AccountMeta::new_readonly(hook.extra_account_meta_list.key(), false),
+        ],
+        data: (instruction::TransferHook { amount: 1 }).data(),
+    };
+    let expected = vec![
+        hook.registry.key(),
+        hook.white_list.key(),
+        hook.kyc_providers.key(),
+        hook.attestation.key(),
+        hook.approval.key(),
+        hook.bond.key(),
+        hook.source_holding.key(),
+        hook.destination_holding.key(),
+        hook.holding_limits.key(),
+        hook.rule_modules.key(),
+        hook.memo_policy.key(),
+        hook.instructions_sysvar.key(),
+        hook.config.key(),
+    ];
+
+    let account_infos = vec![
+        hook.source.account_info(),
+        hook.mint.account_info(),
+        hook.destination.account_info(),
+        hook.owner.account_info(),
+        hook.extra_account_meta_list.account_info(),
+        hook.registry.account_info(),
+        hook.white_list.account_info(),
+        hook.kyc_providers.account_info(),
+        hook.attestation.account_info(),
+        hook.approval.account_info(),
+        hook.bond.account_info(),
+        hook.source_holding.account_info(),
+        hook.destination_holding.account_info(),
+        hook.holding_limits.account_info(),
+        hook.rule_modules.account_info(),
+        hook.memo_policy.account_info(),
+        hook.instructions_sysvar.account_info(),
+        hook.config.account_info(),
+    ];
+    let mut cpi_account_infos = account_infos[..5].to_vec();
+    ExtraAccountMetaList::add_to_cpi_instruction::<ExecuteInstruction>(
+        &mut execute,
+        &mut cpi_account_infos,
+        &meta_list_data,
+        &account_infos
+    ).unwrap();
+
+    let resolved: Vec<Pubkey> = execute.accounts[5..]
+        .iter()
+        .map(|meta| meta.pubkey)
+        .collect();
+    assert_eq!(resolved, expected);
+    assert!(execute.accounts[6].is_writable, "white list is writable");
+}
+
+#[test]
+fn transfer_to_whitelisted_account_is_allowed() {
+    let mut hook = HookAccounts::new(true);
+    hook.whitelist_destination();
+
+    hook.execute(1).unwrap();
+}
+
+#[test]
+fn transfer_to_attested_investor_is_allowed() {
+    let mut hook = HookAccounts::new(true);
+    let provider = Pubkey::new_unique();
+
+    let mut kyc_providers: KycProviders = hook.kyc_providers.deserialize();
+    kyc_providers.providers.push(provider);
+    hook.kyc_providers = TestAccount::anchor(hook.kyc_providers.key(), &kyc_providers);
+    hook.attestation = TestAccount::anchor(hook.attestation.key(), &Attestation {
+        investor: hook.recipient,
+        mint: hook.mint.key(),
+        provider,
+        level: 1,
+        expires_at: NOW + 60,
+        bump: 0,
+    });
+
+    hook.execute(1).unwrap();
+}
+
+#[test]
+fn transfer_to_unlisted_account_is_rejected() {
+    let mut hook = HookAccounts::new(true);
+
+    assert_rejected(hook.execute(1), TransferError::DestinationNotAllowed);
+}
+
+#[test]
+fn expired_attestation_is_rejected() {
+    let mut hook = HookAccounts::new(true);
+    let provider = Pubkey::new_unique();
+
+    let mut kyc_providers: KycProviders = hook.kyc_providers.deserialize();
+    kyc_providers.providers.push(provider);
+    hook.kyc_providers = TestAccount::anchor(hook.kyc_providers.key(), &kyc_providers);
+    hook.attestation = TestAccount::anchor(hook.attestation.key(), &Attestation {
+        investor: hook.recipient,
+        mint: hook.mint.key(),
+        provider,
+        level: 1,
+        expires_at: NOW,
+        bump: 0,
+    });
+
+    assert_rejected(hook.execute(1), TransferError::DestinationNotAllowed);
+}
+
+#[test]
+fn transfer_to_approved_wallet_is_allowed() {
+    let mut hook = HookAccounts::new(true);
+    let compliance_key = Pubkey::new_unique();
+    hook.approve_recipient(compliance_key, compliance_key);
+
+    hook.execute(1).unwrap();
+}
+
+#[test]
+fn approval_signed_by_a_rotated_key_is_rejected() {
+    let mut hook = HookAccounts::new(true);
+    hook.approve_recipient(Pubkey::new_unique(), Pubkey::new_unique());
+
+    assert_rejected(hook.execute(1), TransferError::DestinationNotAllowed);
+}
+
+#[test]
+fn direct_hook_invocation_is_rejected() {
+    // The source is not flagged as transferring outside of a Token-2022 transfer
+    let mut hook = HookAccounts::new(false);
+    hook.whitelist_destination();
+
+    assert_rejected(hook.execute(1), TransferError::IsNotCurrentlyTransferring);
+}
+
+#[test]
+fn transfer_is_rejected_while_halted() {
+    let mut hook = HookAccounts::new(true);
+    hook.whitelist_destination();
+    hook.set_status(SecurityStatus::Halted);
+
+    assert_rejected(hook.execute(1), TransferError::SecurityNotActive);
+}
+
+#[test]
+fn transfer_authority_must_own_or_be_delegated_the_source() {
+    let mut hook = HookAccounts::new(true);
+    hook.whitelist_destination();
+    hook.owner = TestAccount::wallet(Pubkey::new_unique());
+    assert_ne!(hook.owner.key(), hook.sender);
+
+    assert_rejected(hook.execute(1), TransferError::InvalidTransferAuthority);
+}
+
+#[test]
+fn delegated_transfer_is_allowed() {
+    let mut hook = HookAccounts::new(true);
+    hook.whitelist_destination();
+    let delegate = Pubkey::new_unique();
+    hook.source = with_delegate(&hook.source, &delegate, 10);
+    hook.owner = TestAccount::wallet(delegate);
+
+    hook.execute(1).unwrap();
+}
+
+#[test]
+fn permanent_delegate_transfer_is_allowed() {
+    let permanent_delegate = Pubkey::new_unique();
+    let mut hook = HookAccounts::with_permanent_delegate(true, Some(permanent_delegate));
+    hook.whitelist_destination();
+    hook.owner = TestAccount::wallet(permanent_delegate);
+
+    hook.execute(1).unwrap();
+}
+
+#[test]
+fn delegated_transfer_checks_the_destination_owner() {
+    // A whitelisted delegate does not make an unlisted destination acceptable
+    let mut hook = HookAccounts::new(true);
+    let delegate = Pubkey::new_unique();
+    hook.source = with_delegate(&hook.source, &delegate, 10);
+    hook.owner = TestAccount::wallet(delegate);
+    let mut white_list: WhiteList = hook.white_list.deserialize();
+    white_list.white_list.push(delegate);
+    hook.white_list = TestAccount::anchor(hook.white_list.key(), &white_list);
+
+    assert_rejected(hook.execute(1), TransferError::DestinationNotAllowed);
+}
+
+fn rule_module(program: Pubkey, account_count: u8) -> RuleModule {
+    RuleModule { program, account_count }
+}
+
+#[test]
+fn rule_modules_are_called_in_order_with_the_transfer() {
+    let mut hook = HookAccounts::new(true);
+    hook.whitelist_destination();
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let module_account = Pubkey::new_unique();
+    set_cpi_return_data(first, vec![0]);
+    hook.set_rule_modules(
+        vec![rule_module(first, 0), rule_module(second, 1)],
+        vec![
+            TestAccount::program(first),
+            TestAccount::program(second),
+            empty(module_account).writable()
+        ]
+    );
+
+    hook.execute(5).unwrap();
+
+    let invoked = invoked();
+    assert_eq!(
+        invoked.iter().map(|ix| ix.program_id).collect::<Vec<_>>(),
+        vec![first, second]
+    );
+    let accounts: Vec<Pubkey> = invoked[1].accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(accounts, vec![
+        hook.source.key(),
+        hook.mint.key(),
+        hook.destination.key(),
+        hook.owner.key(),
+        module_account,
+    ]);
+    assert!(invoked[1].accounts[4].is_writable);
+    assert!(invoked[1].accounts[..4].iter().all(|meta| !meta.is_writable && !meta.is_signer));
+    assert_eq!(invoked[1].data[8..], 5u64.to_le_bytes());
+}
+
+#[test]
+fn first_rule_module_rejection_aborts_the_transfer() {
+    let mut hook = HookAccounts::new(true);
+    hook.whitelist_destination();
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+    set_cpi_return_data(first, vec![1]);
+    hook.set_rule_modules(
+        vec![rule_module(first, 0), rule_module(second, 0)],
+        vec![TestAccount::program(first), TestAccount::program(second)]
+    );
+
+    assert_rejected(hook.execute(1), TransferError::RuleModuleRejected);
+    assert_eq!(invoked().len(), 1, "later modules are not consulted");
+}
+
+#[test]
+fn rule_modules_run_after_the_built_in_rules() {
+    let mut hook = HookAccounts::new(true);
+    let module = Pubkey::new_unique();
+    hook.set_rule_modules(vec![rule_module(module, 0)], vec![TestAccount::program(module)]);
+
+    assert_rejected(hook.execute(1), TransferError::DestinationNotAllowed);
+    assert!(invoked().is_empty());
+}
+
+#[test]
+fn rule_module_accounts_must_match_the_configuration() {
+    let mut hook = HookAccounts::new(true);
+    hook.whitelist_destination();
+    let module = Pubkey::new_unique();
+    hook.set_rule_modules(vec![rule_module(module, 0)], vec![TestAccount::program(Pubkey::new_unique())]);
+    assert_rejected(hook.execute(1), RuleModuleError::ProgramMismatch);
+
+    hook.set_rule_modules(vec![rule_module(module, 1)], vec![TestAccount::program(module)]);
+    assert_rejected(hook.execute(1), RuleModuleError::MissingAccounts);
+}
+
+#[test]
+fn stale_return_data_is_not_read_as_a_module_answer() {
+    let mut hook = HookAccounts::new(true);
+    hook.whitelist_destination();
+    let module = Pubkey::new_unique();
+    // Left by an earlier call to the module, which answers nothing this time
+    set_return_data(module, vec![1]);
+    hook.set_rule_modules(vec![rule_module(module, 0)], vec![TestAccount::program(module)]);
+
+    hook.execute(1).unwrap();
+}
+
+#[test]
+fn rule_modules_must_lead_the_configured_hook_accounts() {
+    let mut hook = HookAccounts::new(true);
+    let (module, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let fixed = |address| HookAccountConfig {
+        address: HookAccountAddress::Fixed { address },
+        is_signer: false,
+        is_writable: false,
+    };
+    hook.extra_account_meta_list = common::extra_account_meta_list(
+        hook.extra_account_meta_list.key(),
+        &[fixed(module), fixed(other)]
+    );
+
+    hook.configure_rule_modules(vec![rule_module(module, 1)]).unwrap();
+    let rule_modules: RuleModules = hook.rule_modules.deserialize();
+    assert_eq!(rule_modules.modules, vec![rule_module(module, 1)]);
+
+    assert_rejected(
+        hook.configure_rule_modules(vec![rule_module(module, 2)]),
+        RuleModuleError::MissingAccounts
+    );
+    assert_rejected(
+        hook.configure_rule_modules(vec![rule_module(other, 0)]),
+        RuleModuleError::ProgramMismatch
+    );
+    assert_rejected(
+        hook.configure_rule_modules(vec![rule_module(module, 0), rule_module(module, 0)]),
+        RuleModuleError::ProgramMismatch
+    );
+}
+
+#[test]
+fn destination_balance_above_max_balance_is_rejected() {
+    // The destination holds 1 token after the transfer
+    let mut hook = HookAccounts::new(true);
+    hook.whitelist_destination();
+    hook.set_holding_limits(1, 0, 0, Vec::new());
+    hook.execute(1).unwrap();
+
+    hook.destination = token_account(hook.destination.key(), &hook.mint.key(), &hook.recipient, 2, false);
+    assert_rejected(hook.execute(1), HoldingLimitError::LimitExceeded);
+}
+
+#[test]
+fn destination_share_of_supply_is_limited() {
+    // 100 bps of a 5_000_000 supply basis is 50_000 tokens, whatever the current supply
+    let mut hook = HookAccounts::new(true);
+    hook.whitelist_destination();
+    hook.set_holding_limits(0, 100, 5_000_000, Vec::new());
+
+    hook.destination = token_account(hook.destination.key(), &hook.mint.key(), &hook.recipient, 50_000, false);
+    hook.execute(1).unwrap();
+
+    hook.destination = token_account(hook.destination.key(), &hook.mint.key(), &hook.recipient, 50_001, false);
+    assert_rejected(hook.execute(1), HoldingLimitError::LimitExceeded);
+}
+
+#[test]
+fn exempt_accounts_are_not_limited() {
+    let mut hook = HookAccounts::new(true);
+    hook.whitelist_destination();
+    hook.destination = token_account(hook.destination.key(), &hook.mint.key(), &hook.recipient, 500_000, false);
+
+    hook.set_holding_limits(1, 100, 1_000_000, vec![hook.destination.key()]);
+    hook.execute(1).unwrap();
+
+    hook.set_holding_limits(1, 100, 1_000_000, vec![hook.recipient]);
+    hook.execute(1).unwrap();
+}
+
+#[test]
+fn thawed_holder_of_default_frozen_mint_is_allowed() {
+    let mut hook = HookAccounts::new(true);
+    hook.mint = default_frozen_mint(hook.mint.key(), &Pubkey::new_unique());
+    hook.execute(1).unwrap();
+
+    hook.destination = frozen(&hook.destination);
+    assert_rejected(hook.execute(1), TransferError::DestinationNotAllowed);
+}
+
+#[test]
+fn transfer_with_reason_memo_is_allowed() {
+    let mut hook = HookAccounts::new(true);
+    hook.whitelist_destination();
+    hook.require_memos(&["SALE", "GIFT", "INHERITANCE"]);
+    hook.precede_transfer_with(vec![memo("GIFT:to my daughter")]);
+
+    hook.execute(1).unwrap();
+}
+
+#[test]
+fn transfer_without_memo_is_rejected_when_required() {
+    let mut hook = HookAccounts::new(true);
+    hook.whitelist_destination();
+    hook.require_memos(&["SALE"]);
+
+    assert_rejected(hook.execute(1), MemoError::MissingReasonMemo);
+}
+
+#[test]
+fn memo_with_unknown_reason_code_is_rejected() {
+    let mut hook = HookAccounts::new(true);
+    hook.whitelist_destination();
+    hook.require_memos(&["SALE"]);
+    // Only the closest memo to the transfer counts
+    hook.precede_transfer_with(vec![memo("SALE"), memo("SALES")]);
+
+    assert_rejected(hook.execute(1), MemoError::MissingReasonMemo);
+}
+
+#[test]
+fn memo_of_an_earlier_transfer_is_not_reused() {
+    let mut hook = HookAccounts::new(true);
+    hook.whitelist_destination();
+    hook.require_memos(&["SALE"]);
+    let transfer = transfer_instruction(&hook.source.key(), &hook.mint.key(), &hook.destination.key());
+    let instructions = [memo("SALE"), transfer.clone(), transfer];
+
+    hook.instructions_sysvar = instructions_sysvar(&instructions, 1);
+    hook.execute(1).unwrap();
+
+    hook.instructions_sysvar = instructions_sysvar(&instructions, 2);
+    assert_rejected(hook.execute(1), MemoError::MissingReasonMemo);
+}
+
+#[test]
+fn escrow_of_an_ask_into_its_order_vault_is_allowed() {
+    let mut hook = HookAccounts::new(true);
+    let vault = hook.destination.key();
+    hook.precede_transfer_with(vec![post_order(OrderSide::Ask, vault)]);
+
+    hook.execute(1).unwrap();
+}
+
+#[test]
+fn order_vaults_only_receive_the_escrow_of_an_ask() {
+    let mut hook = HookAccounts::new(true);
+    let vault = hook.destination.key();
+
+    hook.precede_transfer_with(vec![post_order(OrderSide::Bid, vault)]);
+    assert_rejected(hook.execute(1), TransferError::DestinationNotAllowed);
+
+    hook.precede_transfer_with(vec![post_order(OrderSide::Ask, Pubkey::new_unique())]);
+    assert_rejected(hook.execute(1), TransferError::DestinationNotAllowed);
+
+    // The escrow must be the transfer right after the ask
+    hook.precede_transfer_with(vec![post_order(OrderSide::Ask, vault), memo("SALE")]);
+    assert_rejected(hook.execute(1), TransferError::DestinationNotAllowed);
+}
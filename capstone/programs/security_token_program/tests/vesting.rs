@@ -0,0 +1,187 @@
+mod common;
+
+use anchor_lang::{ prelude::*, solana_program::entrypoint::ProgramResult, InstructionData };
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_2022::spl_token_2022,
+};
+use common::*;
+use security_token_program::{
+    instruction,
+    Config,
+    Milestone,
+    Registry,
+    RegistryError,
+    SaleStatus,
+    SecurityStatus,
+    Vesting,
+    VestingSchedule,
+    ID as PROGRAM_ID,
+};
+
+const GRANT: u64 = 400;
+const SOLD: u64 = 1_000;
+
+struct VestingAccounts {
+    issuer: TestAccount,
+    registry: TestAccount,
+    config: TestAccount,
+    mint: TestAccount,
+    vesting: TestAccount,
+    vault: TestAccount,
+    bond: TestAccount,
+    vault_holding: TestAccount,
+    audit_log: TestAccount,
+    token_program: TestAccount,
+}
+
+impl VestingAccounts {
+    fn new(schedule: VestingSchedule) -> Self {
+        let issuer = Pubkey::new_unique();
+        let beneficiary = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let (registry, registry_bump) = Pubkey::find_program_address(&[b"registry", mint.as_ref()], &PROGRAM_ID);
+        let (config, config_bump) = Pubkey::find_program_address(&[b"config", mint.as_ref()], &PROGRAM_ID);
+        let (vesting, vesting_bump) = Pubkey::find_program_address(
+            &[b"vesting", mint.as_ref(), beneficiary.as_ref(), 0u64.to_le_bytes().as_ref()],
+            &PROGRAM_ID
+        );
+        let vault = get_associated_token_address_with_program_id(&vesting, &mint, &spl_token_2022::ID);
+
+        VestingAccounts {
+            issuer: TestAccount::wallet(issuer).signer(),
+            registry: TestAccount::anchor(registry, &Registry {
+                mint,
+                admin: issuer,
+                status: SecurityStatus::Active,
+                extra_account_meta_list: pda(&[b"extra-account-metas", mint.as_ref()]),
+                white_list: pda(&[b"white_list", mint.as_ref()]),
+                kyc_providers: pda(&[b"kyc_providers", mint.as_ref()]),
+                config,
+                fee_config: Pubkey::default(),
+                bump: registry_bump,
+            }),
+            config: TestAccount::anchor(config, &Config {
+                owner: issuer,
+                mint,
+                compliance_key: Pubkey::new_unique(),
+                max_supply: 10_000,
+                total_sold: SOLD,
+                tranches: Vec::new(),
+                escrow_payments: false,
+                payment_mint: Pubkey::default(),
+                soft_cap: 0,
+                close_ts: 0,
+                total_raised: 0,
+                status: SaleStatus::Open,
+                bump: config_bump,
+            }).writable(),
+            mint: hooked_mint(mint, &issuer, None).writable(),
+            vesting: TestAccount::anchor(vesting, &Vesting {
+                issuer,
+                beneficiary,
+                mint,
+                seed: 0,
+                total_amount: GRANT,
+                released: 0,
+                revoked: false,
+                schedule,
+                bump: vesting_bump,
+            }).writable(),
+            vault: token_account(vault, &mint, &vesting, GRANT, false).writable(),
+            bond: empty(pda(&[b"bond", mint.as_ref()])),
+            vault_holding: empty(pda(&[b"bond_holding", vault.as_ref()])).writable(),
+            audit_log: audit_log(pda(&[b"audit_log", mint.as_ref()]), &mint).writable(),
+            token_program: TestAccount::program(spl_token_2022::ID),
+        }
+    }
+
+    fn revoke(&mut self) -> ProgramResult {
+        let data = (instruction::RevokeVesting { reason_hash: [0; 32] }).data();
+        process(
+            &mut [
+                &mut self.issuer,
+                &mut self.registry,
+                &mut self.config,
+                &mut self.mint,
+                &mut self.vesting,
+                &mut self.vault,
+                &mut self.bond,
+                &mut self.vault_holding,
+                &mut self.audit_log,
+                &mut self.token_program,
+            ],
+            &data
+        )
+    }
+
+    fn achieve(&mut self, index: u8) -> ProgramResult {
+        let data = (instruction::AchieveMilestone { index, reason_hash: [0; 32] }).data();
+        process(
+            &mut [&mut self.issuer, &mut self.registry, &mut self.config, &mut self.vesting, &mut self.audit_log],
+            &data
+        )
+    }
+}
+
+fn milestones() -> VestingSchedule {
+    VestingSchedule::Milestone {
+        milestones: vec![
+            Milestone { amount: GRANT / 2, achieved: false },
+            Milestone { amount: GRANT / 2, achieved: false }
+        ],
+    }
+}
+
+#[test]
+fn revoking_returns_the_unvested_grant_to_the_supply_cap() {
+    let mut accounts = VestingAccounts::new(VestingSchedule::Cliff { cliff_ts: NOW + 1 });
+
+    accounts.revoke().unwrap();
+
+    let config: Config = accounts.config.deserialize();
+    assert_eq!(config.total_sold, SOLD - GRANT);
+    let vesting: Vesting = accounts.vesting.deserialize();
+    assert!(vesting.revoked);
+    assert_eq!(vesting.total_amount, 0);
+}
+
+#[test]
+fn revoking_keeps_the_vested_part_sold() {
+    let mut accounts = VestingAccounts::new(milestones());
+    accounts.achieve(0).unwrap();
+
+    accounts.revoke().unwrap();
+
+    let config: Config = accounts.config.deserialize();
+    assert_eq!(config.total_sold, SOLD - GRANT / 2);
+}
+
+#[test]
+fn milestones_are_only_achieved_on_the_vesting_pda() {
+    let mut accounts = VestingAccounts::new(milestones());
+    let data = accounts.vesting.data().to_vec();
+    accounts.vesting = TestAccount::new(Pubkey::new_unique(), PROGRAM_ID, &data).writable();
+
+    assert_eq!(accounts.achieve(0).unwrap_err(), program_error(ErrorCode::ConstraintSeeds));
+}
+
+#[test]
+fn milestones_are_only_achieved_through_the_mint_config() {
+    let mut accounts = VestingAccounts::new(milestones());
+    let mut config: Config = accounts.config.deserialize();
+    config.mint = Pubkey::new_unique();
+    accounts.config = TestAccount::anchor(Pubkey::new_unique(), &config);
+
+    assert_eq!(accounts.achieve(0).unwrap_err(), program_error(ErrorCode::ConstraintHasOne));
+}
+
+#[test]
+fn milestones_are_not_achieved_on_a_halted_security() {
+    let mut accounts = VestingAccounts::new(milestones());
+    let mut registry: Registry = accounts.registry.deserialize();
+    registry.status = SecurityStatus::Halted;
+    accounts.registry = TestAccount::anchor(accounts.registry.key(), &registry);
+
+    assert_eq!(accounts.achieve(0).unwrap_err(), program_error(RegistryError::NotActive));
+}
@@ -14,6 +14,7 @@ import {
 } from "@solana/spl-token";
 import {
   Keypair,
+  PublicKey,
   SystemProgram,
   Transaction,
   sendAndConfirmTransaction,
@@ -44,6 +45,20 @@ describe("SecurityTokenProgram", () => {
     ASSOCIATED_TOKEN_PROGRAM_ID
   );
 
+  const [registry] = PublicKey.findProgramAddressSync(
+    [Buffer.from("registry"), mint.publicKey.toBuffer()],
+    program.programId
+  );
+  const [whiteList] = PublicKey.findProgramAddressSync(
+    [Buffer.from("white_list"), mint.publicKey.toBuffer()],
+    program.programId
+  );
+  const [auditLog] = PublicKey.findProgramAddressSync(
+    [Buffer.from("audit_log"), mint.publicKey.toBuffer()],
+    program.programId
+  );
+  const reasonHash = Array(32).fill(0);
+
   const recipient = Keypair.generate();
   const destinationTokenAccount = getAssociatedTokenAddressSync(
     mint.publicKey,
@@ -132,7 +147,7 @@ describe("SecurityTokenProgram", () => {
 
   it("Create ExtraAccountMetaList Account", async () => {
     const initializeExtraAccountMetaListInstruction = await program.methods
-      .initializeExtraAccountMetaList()
+      .initializeExtraAccountMetaList([])
       .accounts({
         mint: mint.publicKey,
       })
@@ -152,6 +167,19 @@ describe("SecurityTokenProgram", () => {
     console.log("Transaction Signature:", txSig);
   });
 
+  it("Activate Security", async () => {
+    const txSig = await program.methods
+      .setRegistryStatus({ active: {} }, reasonHash)
+      .accounts({
+        admin: wallet.publicKey,
+        registry,
+        auditLog,
+      })
+      .rpc();
+
+    console.log("Security Active:", txSig);
+  });
+
   it("Transfer tokens to not whitelisted account", async () => {
     const amount = 1 * 10 ** decimals;
     const bigIntAmount = BigInt(amount);
@@ -183,9 +211,12 @@ describe("SecurityTokenProgram", () => {
 
   it("Whitelist Account", async () => {
     const addAccountToWhiteListInstruction = await program.methods
-      .whitelistAdd()
+      .whitelistAdd(reasonHash)
       .accounts({
         newAccount: destinationTokenAccount,
+        registry,
+        whiteList,
+        auditLog,
         signer: wallet.publicKey,
       })
       .instruction();
@@ -4,7 +4,7 @@ use anchor_spl::{
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use crate::Escrow;
+use crate::{error::EscrowError, Escrow};
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -56,13 +56,39 @@ pub struct Make<'info> {
 }
 
 impl<'info> Make<'info> {
-    pub fn save_escrow(&mut self, seed: u64, receive: u64, bumps: &MakeBumps) -> Result<()> {
+    pub fn save_escrow(
+        &mut self,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        expires_at: Option<i64>,
+        crank_tip: u64,
+        bumps: &MakeBumps,
+    ) -> Result<()> {
+        require!(deposit > 0, EscrowError::ZeroAmount);
+        if let Some(expires_at) = expires_at {
+            require!(
+                expires_at > Clock::get()?.unix_timestamp,
+                EscrowError::InvalidExpiry
+            );
+        }
+        // The tip comes out of the escrow rent, so the maker never funds it separately
+        require!(
+            crank_tip <= self.escrow.get_lamports(),
+            EscrowError::TipTooLarge
+        );
+
         self.escrow.set_inner(Escrow {
             seed,
             maker: self.maker.key(),
             mint_a: self.mint_a.key(),
             mint_b: self.mint_b.key(),
+            deposit,
             receive,
+            taken: 0,
+            paid: 0,
+            expires_at,
+            crank_tip,
             bump: bumps.escrow,
         });
         Ok(())
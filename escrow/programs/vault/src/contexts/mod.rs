@@ -6,3 +6,6 @@ pub use take::*;
 
 pub mod refund;
 pub use refund::*;
+
+pub mod crank_expired;
+pub use crank_expired::*;
@@ -8,7 +8,7 @@ use anchor_spl::{
     },
 };
 
-use crate::Escrow;
+use crate::{error::EscrowError, Escrow};
 
 #[derive(Accounts)]
 pub struct Take<'info> {
@@ -47,12 +47,13 @@ pub struct Take<'info> {
     )]
     pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    // Closed to the maker once the vault is fully taken
     #[account(
         mut,
-        close = maker,
         has_one = maker,
         has_one = mint_a,
         has_one = mint_b,
+        constraint = !escrow.is_expired(Clock::get()?.unix_timestamp) @ EscrowError::Expired,
         seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
         bump = escrow.bump
     )]
@@ -74,7 +75,20 @@ pub struct Take<'info> {
 }
 
 impl<'info> Take<'info> {
-    pub fn deposit(&mut self) -> Result<()> {
+    // Pays the maker for `amount` of mint A and records the fill
+    pub fn deposit(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, EscrowError::ZeroAmount);
+        require!(
+            amount <= self.escrow.deposit - self.escrow.taken,
+            EscrowError::InsufficientDeposit
+        );
+        let payment = self
+            .escrow
+            .payment_for(amount)
+            .ok_or(EscrowError::MathOverflow)?;
+        self.escrow.taken += amount;
+        self.escrow.paid += payment;
+
         let transfer_accounts = TransferChecked {
             from: self.taker_ata_b.to_account_info(),
             mint: self.mint_b.to_account_info(),
@@ -84,10 +98,15 @@ impl<'info> Take<'info> {
 
         let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
 
-        transfer_checked(cpi_ctx, self.escrow.receive, self.mint_b.decimals)
+        transfer_checked(cpi_ctx, payment, self.mint_b.decimals)
     }
 
-    pub fn withdraw_and_close_vault(&mut self) -> Result<()> {
+    // Releases `amount` of mint A, closing the vault and the escrow on the last fill.
+    // The last fill also takes anything sent to the vault on top of the deposit.
+    pub fn withdraw_and_close_vault(&mut self, amount: u64) -> Result<()> {
+        let last_fill = self.escrow.taken == self.escrow.deposit;
+        let amount = if last_fill { self.vault.amount } else { amount };
+
         let signer_seeds: [&[&[u8]]; 1] = [&[
             b"escrow",
             self.maker.to_account_info().key.as_ref(),
@@ -107,7 +126,10 @@ impl<'info> Take<'info> {
             accounts,
             &signer_seeds,
         );
-        transfer_checked(ctx, self.vault.amount, self.mint_a.decimals)?;
+        transfer_checked(ctx, amount, self.mint_a.decimals)?;
+        if !last_fill {
+            return Ok(());
+        }
 
         let accounts = CloseAccount {
             account: self.vault.to_account_info(),
@@ -120,6 +142,7 @@ impl<'info> Take<'info> {
             accounts,
             &signer_seeds,
         );
-        close_account(ctx)
+        close_account(ctx)?;
+        self.escrow.close(self.maker.to_account_info())
     }
 }
\ No newline at end of file
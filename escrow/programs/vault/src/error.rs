@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum EscrowError {
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("Crank tip cannot exceed the escrow rent")]
+    TipTooLarge,
+    #[msg("Escrow has expired")]
+    Expired,
+    #[msg("Escrow has not expired")]
+    NotExpired,
+    #[msg("Amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("Amount exceeds what is left in the vault")]
+    InsufficientDeposit,
+    #[msg("Math operation overflow")]
+    MathOverflow,
+}
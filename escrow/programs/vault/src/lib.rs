@@ -6,21 +6,35 @@ pub mod state;
 pub use state::*;
 pub mod contexts;
 pub use contexts::*;
+pub mod error;
 
 #[program]
 pub mod escrow {
     use super::*;
 
-    pub fn make(ctx: Context<Make>, seed: u64, deposit: u64, receive: u64) -> Result<()> {
+    pub fn make(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        expires_at: Option<i64>,
+        crank_tip: u64,
+    ) -> Result<()> {
         ctx.accounts.deposit(deposit)?;
-        ctx.accounts.save_escrow(seed, receive, &ctx.bumps)
+        ctx.accounts
+            .save_escrow(seed, deposit, receive, expires_at, crank_tip, &ctx.bumps)
     }
-    pub fn take(ctx: Context<Take>) -> Result<()> {
-        ctx.accounts.deposit()?;
-        ctx.accounts.withdraw_and_close_vault()
+    pub fn take(ctx: Context<Take>, amount: u64) -> Result<()> {
+        ctx.accounts.deposit(amount)?;
+        ctx.accounts.withdraw_and_close_vault(amount)
     }
 
     pub fn refund(ctx: Context<Refund>) -> Result<()> {
         ctx.accounts.refund_and_close_vault()
     }
+
+    pub fn crank_expired(ctx: Context<CrankExpired>) -> Result<()> {
+        ctx.accounts.pay_tip()?;
+        ctx.accounts.return_and_close_vault()
+    }
 }
@@ -7,7 +7,36 @@ pub struct Escrow {
     pub maker: Pubkey,
     pub mint_a: Pubkey,
     pub mint_b: Pubkey,
+    // Mint A offered and Mint B asked for all of it
+    pub deposit: u64,
     pub receive: u64,
+    // Mint A taken and Mint B paid for it over the fills so far
+    pub taken: u64,
+    pub paid: u64,
+    // Offers past this time can no longer be taken and may be cranked by anyone
+    pub expires_at: Option<i64>,
+    // Lamports of the escrow rent paid to whoever cranks the expired offer
+    pub crank_tip: u64,
     pub bump: u8,
 }
 
+impl Escrow {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    // Mint B owed for taking `amount` more of mint A. Fills are priced on the
+    // running total and rounded up, so the maker is never paid below the offered
+    // rate, rounding never compounds across fills and the last fill pays exactly
+    // what is left of `receive`.
+    pub fn payment_for(&self, amount: u64) -> Option<u64> {
+        let taken = self.taken.checked_add(amount)?;
+        if self.deposit == 0 || taken > self.deposit {
+            return None;
+        }
+        let owed = (taken as u128)
+            .checked_mul(self.receive as u128)?
+            .div_ceil(self.deposit as u128);
+        u64::try_from(owed).ok()?.checked_sub(self.paid)
+    }
+}
@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use escrow::Escrow;
+
+fn escrow(deposit: u64, receive: u64) -> Escrow {
+    Escrow {
+        seed: 0,
+        maker: Pubkey::new_unique(),
+        mint_a: Pubkey::new_unique(),
+        mint_b: Pubkey::new_unique(),
+        deposit,
+        receive,
+        taken: 0,
+        paid: 0,
+        expires_at: None,
+        crank_tip: 0,
+        bump: 0,
+    }
+}
+
+// Takes `amount` the way `take` does, returning the payment
+fn fill(escrow: &mut Escrow, amount: u64) -> u64 {
+    let payment = escrow.payment_for(amount).unwrap();
+    escrow.taken += amount;
+    escrow.paid += payment;
+    payment
+}
+
+#[test]
+fn partial_fills_round_up_for_the_maker() {
+    let mut escrow = escrow(3, 10);
+
+    assert_eq!(fill(&mut escrow, 1), 4);
+    assert_eq!(fill(&mut escrow, 1), 3);
+    assert_eq!(fill(&mut escrow, 1), 3);
+    assert_eq!(escrow.paid, 10);
+}
+
+#[test]
+fn unit_fills_do_not_drain_what_the_maker_is_owed() {
+    let mut escrow = escrow(1_000, 10);
+
+    let early: u64 = (0..10).map(|_| fill(&mut escrow, 1)).sum();
+    assert_eq!(early, 1, "rounding up is only paid once");
+
+    // The rest still pays the offered rate, so it is not taken for free
+    assert_eq!(fill(&mut escrow, 990), 9);
+    assert_eq!(escrow.paid, 10);
+}
+
+#[test]
+fn maker_is_never_paid_below_the_offered_rate() {
+    let mut escrow = escrow(997, 1_009);
+
+    for amount in [1, 2, 3, 5, 7, 11, 13, 100, 500] {
+        fill(&mut escrow, amount);
+        assert!(escrow.paid as u128 * 997 >= escrow.taken as u128 * 1_009);
+    }
+    let rest = 997 - escrow.taken;
+    fill(&mut escrow, rest);
+    assert_eq!(escrow.paid, 1_009, "the last fill pays exactly what is left");
+}
+
+#[test]
+fn fills_beyond_the_deposit_are_not_priced() {
+    let mut escrow = escrow(10, 10);
+    fill(&mut escrow, 4);
+
+    assert_eq!(escrow.payment_for(7), None);
+    assert_eq!(escrow.payment_for(6), Some(6));
+}
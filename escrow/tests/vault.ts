@@ -1,16 +1,289 @@
 import * as anchor from "@coral-xyz/anchor";
-import { Program } from "@coral-xyz/anchor";
-import { Vault } from "../target/types/vault";
+import { BN, Program } from "@coral-xyz/anchor";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import { assert } from "chai";
+import { Escrow } from "../target/types/escrow";
 
-describe("vault", () => {
-  // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+describe("escrow", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
-  const program = anchor.workspace.Vault as Program<Vault>;
+  const program = anchor.workspace.Escrow as Program<Escrow>;
+  const connection = provider.connection;
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+  // Fees are paid by the provider wallet, so lamport balances of the parties
+  // below only move by rent and tips
+  const maker = Keypair.generate();
+  const taker = Keypair.generate();
+  const cranker = Keypair.generate();
+  let mintA: PublicKey;
+  let mintB: PublicKey;
+  let seed = 0;
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM_ID);
+
+  const escrowOf = (offerSeed: number) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        maker.publicKey.toBuffer(),
+        new BN(offerSeed).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  const balance = async (tokenAccount: PublicKey) =>
+    Number((await getAccount(connection, tokenAccount)).amount);
+
+  const now = async () =>
+    (await connection.getBlockTime(await connection.getSlot())) as number;
+
+  // Waits until the cluster clock has passed `timestamp`
+  const waitUntil = async (timestamp: number) => {
+    while ((await now()) <= timestamp) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (error) {
+      assert.equal(error.error?.errorCode?.code, code);
+      return;
+    }
+    assert.fail(`expected ${code}`);
+  };
+
+  const make = async (
+    deposit: number,
+    receive: number,
+    expiresAt: number | null,
+    crankTip: number
+  ) => {
+    const offerSeed = seed++;
+    await program.methods
+      .make(
+        new BN(offerSeed),
+        new BN(deposit),
+        new BN(receive),
+        expiresAt === null ? null : new BN(expiresAt),
+        new BN(crankTip)
+      )
+      .accountsPartial({
+        maker: maker.publicKey,
+        mintA,
+        mintB,
+        makerAtaA: ata(mintA, maker.publicKey),
+        escrow: escrowOf(offerSeed),
+        vault: ata(mintA, escrowOf(offerSeed)),
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([maker])
+      .rpc();
+    return escrowOf(offerSeed);
+  };
+
+  const take = (escrow: PublicKey, amount: number) =>
+    program.methods
+      .take(new BN(amount))
+      .accountsPartial({
+        taker: taker.publicKey,
+        maker: maker.publicKey,
+        mintA,
+        mintB,
+        takerAtaA: ata(mintA, taker.publicKey),
+        takerAtaB: ata(mintB, taker.publicKey),
+        makerAtaB: ata(mintB, maker.publicKey),
+        escrow,
+        vault: ata(mintA, escrow),
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([taker])
+      .rpc();
+
+  const crank = (escrow: PublicKey) =>
+    program.methods
+      .crankExpired()
+      .accountsPartial({
+        cranker: cranker.publicKey,
+        maker: maker.publicKey,
+        mintA,
+        makerAtaA: ata(mintA, maker.publicKey),
+        escrow,
+        vault: ata(mintA, escrow),
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([cranker])
+      .rpc();
+
+  before(async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const fund = new Transaction();
+    for (const party of [maker, taker, cranker]) {
+      fund.add(
+        SystemProgram.transfer({
+          fromPubkey: payer.publicKey,
+          toPubkey: party.publicKey,
+          lamports: LAMPORTS_PER_SOL,
+        })
+      );
+    }
+    await provider.sendAndConfirm(fund);
+
+    mintA = await createMint(connection, payer, payer.publicKey, null, 0);
+    mintB = await createMint(connection, payer, payer.publicKey, null, 0);
+    const makerAtaA = await getOrCreateAssociatedTokenAccount(
+      connection,
+      payer,
+      mintA,
+      maker.publicKey
+    );
+    const takerAtaB = await getOrCreateAssociatedTokenAccount(
+      connection,
+      payer,
+      mintB,
+      taker.publicKey
+    );
+    await mintTo(connection, payer, mintA, makerAtaA.address, payer, 1_000_000);
+    await mintTo(connection, payer, mintB, takerAtaB.address, payer, 1_000_000);
+  });
+
+  it("rejects an expiry that is not in the future", async () => {
+    await expectError(make(100, 50, (await now()) - 1, 0), "InvalidExpiry");
+  });
+
+  it("takes an offer before it expires", async () => {
+    const escrow = await make(100, 50, (await now()) + 60, 0);
+    const makerB = await balance(ata(mintB, maker.publicKey)).catch(() => 0);
+
+    await take(escrow, 100);
+
+    assert.equal(await balance(ata(mintB, maker.publicKey)), makerB + 50);
+    assert.isNull(await connection.getAccountInfo(escrow));
+  });
+
+  it("prices partial fills on the running total", async () => {
+    const escrow = await make(1_000, 10, null, 0);
+    const makerB = await balance(ata(mintB, maker.publicKey));
+
+    // Rounding up the first unit covers the next nine
+    for (let i = 0; i < 10; i++) {
+      await take(escrow, 1);
+    }
+    assert.equal(await balance(ata(mintB, maker.publicKey)), makerB + 1);
+
+    // The rest is still paid at the offered rate
+    await take(escrow, 990);
+    assert.equal(await balance(ata(mintB, maker.publicKey)), makerB + 10);
+  });
+
+  it("rejects a fill larger than what is left", async () => {
+    const escrow = await make(100, 50, null, 0);
+    await take(escrow, 40);
+
+    await expectError(take(escrow, 61), "InsufficientDeposit");
+  });
+
+  it("closes the vault and the escrow on the last fill", async () => {
+    const escrow = await make(100, 50, null, 0);
+    const vault = ata(mintA, escrow);
+    await take(escrow, 40);
+    assert.equal(await balance(vault), 60);
+    assert.isNotNull(await connection.getAccountInfo(escrow));
+
+    const escrowRent = await connection.getBalance(escrow);
+    const vaultRent = await connection.getBalance(vault);
+    const makerLamports = await connection.getBalance(maker.publicKey);
+    const takerLamports = await connection.getBalance(taker.publicKey);
+    const takerA = await balance(ata(mintA, taker.publicKey));
+
+    await take(escrow, 60);
+
+    assert.equal(await balance(ata(mintA, taker.publicKey)), takerA + 60);
+    assert.isNull(await connection.getAccountInfo(vault));
+    assert.isNull(await connection.getAccountInfo(escrow));
+    assert.equal(
+      await connection.getBalance(maker.publicKey),
+      makerLamports + escrowRent
+    );
+    assert.equal(
+      await connection.getBalance(taker.publicKey),
+      takerLamports + vaultRent
+    );
+  });
+
+  it("rejects a take after expiry", async () => {
+    const expiresAt = (await now()) + 2;
+    const escrow = await make(100, 50, expiresAt, 0);
+    await waitUntil(expiresAt);
+
+    await expectError(take(escrow, 100), "Expired");
+    assert.equal(await balance(ata(mintA, escrow)), 100);
+  });
+
+  it("rejects cranking an offer that has not expired", async () => {
+    const escrow = await make(100, 50, (await now()) + 60, 1_000);
+
+    await expectError(crank(escrow), "NotExpired");
+  });
+
+  it("rejects cranking an offer without expiry", async () => {
+    const escrow = await make(100, 50, null, 1_000);
+
+    await expectError(crank(escrow), "NotExpired");
+  });
+
+  it("rejects a tip larger than the escrow rent", async () => {
+    await expectError(make(100, 50, null, LAMPORTS_PER_SOL), "TipTooLarge");
+  });
+
+  it("cranks an expired offer, paying the tip out of the escrow rent", async () => {
+    const tip = 100_000;
+    const expiresAt = (await now()) + 2;
+    const escrow = await make(100, 50, expiresAt, tip);
+    const vault = ata(mintA, escrow);
+    const escrowRent = await connection.getBalance(escrow);
+    const vaultRent = await connection.getBalance(vault);
+    const makerLamports = await connection.getBalance(maker.publicKey);
+    const crankerLamports = await connection.getBalance(cranker.publicKey);
+    const makerA = await balance(ata(mintA, maker.publicKey));
+    await waitUntil(expiresAt);
+
+    await crank(escrow);
+
+    assert.equal(
+      await connection.getBalance(cranker.publicKey),
+      crankerLamports + tip
+    );
+    assert.equal(
+      await connection.getBalance(maker.publicKey),
+      makerLamports + escrowRent - tip + vaultRent
+    );
+    assert.equal(await balance(ata(mintA, maker.publicKey)), makerA + 100);
+    assert.isNull(await connection.getAccountInfo(escrow));
+    assert.isNull(await connection.getAccountInfo(vault));
   });
 });